@@ -0,0 +1,16 @@
+-- Amounts were DECIMAL, which holds neither 18 decimal claims (NUMERIC is DECIMAL(18, 3)) nor
+-- u128 values from 10^38.  They are stored as decimal text instead.  Text amounts do not order
+-- numerically, so the claimed amount index is not recreated, and the other indexes are
+-- dropped while the tables are altered.
+DROP INDEX IF EXISTS rewards_claimed_id_index;
+DROP INDEX IF EXISTS rewards_claimed_claimed_index;
+ALTER TABLE rewards_claimed ALTER claimed_amount TYPE VARCHAR USING CAST(CAST(claimed_amount AS HUGEINT) AS VARCHAR);
+CREATE INDEX rewards_claimed_id_index ON rewards_claimed (id, root);
+
+DROP INDEX IF EXISTS rewards_submission_avs_token_index;
+ALTER TABLE rewards_submission ALTER amount TYPE VARCHAR USING CAST(CAST(amount AS HUGEINT) AS VARCHAR);
+CREATE INDEX rewards_submission_avs_token_index ON rewards_submission (avs, token, start_timestamp);
+
+DROP INDEX IF EXISTS rewards_submission_strategy_hash_index;
+ALTER TABLE rewards_submission_strategy ALTER multiplier TYPE VARCHAR USING CAST(CAST(multiplier AS HUGEINT) AS VARCHAR);
+CREATE INDEX rewards_submission_strategy_hash_index ON rewards_submission_strategy (rewards_submission_hash);
@@ -0,0 +1,19 @@
+-- Claims are identified by the transaction and log that emitted them, so rerunning over the
+-- same blocks inserts nothing twice.  Claims loaded before this, and those loaded from JSON
+-- without the log, have no identity and are kept as they are.  Submissions are identified by
+-- their hash; copies of one written by earlier reruns are removed, keeping the first.  Indexes
+-- are dropped while the tables are altered.
+DROP INDEX IF EXISTS rewards_claimed_id_index;
+ALTER TABLE rewards_claimed ADD COLUMN block_number BIGINT;
+ALTER TABLE rewards_claimed ADD COLUMN transaction_hash CHAR(64);
+ALTER TABLE rewards_claimed ADD COLUMN log_index BIGINT;
+CREATE INDEX rewards_claimed_id_index ON rewards_claimed (id, root);
+CREATE UNIQUE INDEX rewards_claimed_event_index ON rewards_claimed (transaction_hash, log_index);
+
+DELETE FROM rewards_submission WHERE rewards_submission_hash IS NOT NULL AND id NOT IN (
+    SELECT MIN(id) FROM rewards_submission GROUP BY rewards_submission_hash
+);
+DELETE FROM rewards_submission_strategy WHERE rewards_submission_hash IS NOT NULL AND id NOT IN (
+    SELECT MIN(id) FROM rewards_submission_strategy GROUP BY rewards_submission_hash, strategy
+);
+CREATE UNIQUE INDEX rewards_submission_hash_index ON rewards_submission (rewards_submission_hash);
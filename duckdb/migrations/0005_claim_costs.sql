@@ -0,0 +1,10 @@
+-- What each claim's transaction cost, when the indexer fetched its receipt.  The fee paid is the
+-- claim's share of the transaction fee, so fees sum to what claiming cost.  The gas price and fee
+-- are decimal text like the amounts.  Indexes are dropped while the table is altered.
+DROP INDEX IF EXISTS rewards_claimed_id_index;
+DROP INDEX IF EXISTS rewards_claimed_event_index;
+ALTER TABLE rewards_claimed ADD COLUMN gas_used BIGINT;
+ALTER TABLE rewards_claimed ADD COLUMN effective_gas_price VARCHAR;
+ALTER TABLE rewards_claimed ADD COLUMN fee_paid VARCHAR;
+CREATE INDEX rewards_claimed_id_index ON rewards_claimed (id, root);
+CREATE UNIQUE INDEX rewards_claimed_event_index ON rewards_claimed (transaction_hash, log_index);
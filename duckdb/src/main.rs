@@ -1,18 +1,24 @@
-mod quack;
+use duckdb_store::quack;
 
 #[tokio::main]
 async fn main() {
     init_logging();
 
+    // open the database file, so migrations and loads persist between runs
+    let mut duck_client = quack::open("rewards_claimed_duckdb.db").await.unwrap();
+    if std::env::args().nth(1).as_deref() == Some("migrate") {
+        migrate(&mut duck_client).await.unwrap();
+        return;
+    }
+    quack::migrate_up(&mut duck_client).await.unwrap();
+
     // File paths
     let json_file_path = "rewards_claimed.json";
+    let submission_file_path = "rewards_submissions.json";
 
     // Read the JSON file
     let rewards_claimed = read_reward_json(json_file_path).await.unwrap();
-
-    // connect db
-    let mut duck_client = quack::init().await.unwrap();
-    quack::create_tables(&mut duck_client).await.unwrap();
+    let rewards_submissions = read_submission_json(submission_file_path).await.unwrap();
 
     let write_instant = std::time::Instant::now();
     // Write the pg data
@@ -20,6 +26,9 @@ async fn main() {
         .await
         .unwrap();
     println!("Write time: {:?} us", write_instant.elapsed().as_micros());
+    quack::write_rewards_submissions(&mut duck_client, &rewards_submissions)
+        .await
+        .unwrap();
 
     let read_instant = std::time::Instant::now();
     let stat = quack::read_rewards_claimed_stat(&mut duck_client)
@@ -30,6 +39,12 @@ async fn main() {
         read_instant.elapsed().as_micros()
     );
     println!("Claimed amount stat: {:?}", stat);
+    let cost_stat = quack::read_claim_cost_stat(&mut duck_client).await.unwrap();
+    println!("Claim cost stat: {:?}", cost_stat);
+    let offered = quack::read_rewards_offered(&mut duck_client, 0, u32::MAX)
+        .await
+        .unwrap();
+    println!("Rewards offered: {:?}", offered);
 
     println!(
         "Table size in postgres: {:?}",
@@ -37,6 +52,32 @@ async fn main() {
     );
 }
 
+/// `duckdb migrate up` applies the pending migrations, `duckdb migrate status` lists them.
+async fn migrate(duck_client: &mut quack::DuckdbClient) -> eyre::Result<()> {
+    match std::env::args().nth(2).as_deref() {
+        Some("up") => {
+            let applied = quack::migrate_up(duck_client).await?;
+            println!("Applied migrations: {:?}", applied);
+        }
+        Some("status") => {
+            for migration in quack::migration_status(duck_client).await? {
+                println!(
+                    "{:04} {:<24} {}",
+                    migration.version,
+                    migration.name,
+                    if migration.applied {
+                        "applied"
+                    } else {
+                        "pending"
+                    }
+                );
+            }
+        }
+        _ => return Err(eyre::eyre!("Usage: duckdb migrate up|status")),
+    }
+    Ok(())
+}
+
 fn init_logging() {
     tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -51,3 +92,11 @@ async fn read_reward_json(path: &str) -> eyre::Result<Vec<eigen_types::RewardsCl
     println!("{:?}", rewards_claimed.len());
     Ok(rewards_claimed)
 }
+
+async fn read_submission_json(path: &str) -> eyre::Result<Vec<eigen_types::RewardsSubmission>> {
+    let rewards_submissions = std::fs::read_to_string(path)?;
+    let rewards_submissions: Vec<eigen_types::RewardsSubmission> =
+        serde_json::from_str(&rewards_submissions)?;
+    println!("{:?}", rewards_submissions.len());
+    Ok(rewards_submissions)
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct RewardsClaimed {
     pub root: [u8; 32],
     pub earner: String,
@@ -9,6 +9,122 @@ pub struct RewardsClaimed {
     pub token: String,
     #[serde(rename = "claimedAmount")]
     pub claimed_amount: u128,
+    #[serde(rename = "blockNumber", default)]
+    pub block_number: u64,
+    #[serde(rename = "transactionHash", default)]
+    pub transaction_hash: [u8; 32],
+    #[serde(rename = "logIndex", default)]
+    pub log_index: u64,
+    /// The cost of the claim transaction, when receipts were fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<ClaimCost>,
+}
+
+impl RewardsClaimed {
+    /// Whether the claim records the log it was decoded from, which identifies it across reruns.
+    /// Claims read from JSON written before the log was recorded have an all-zero hash.
+    pub fn has_event_id(&self) -> bool {
+        self.transaction_hash != [0; 32]
+    }
+}
+
+/// What the `processClaim` transaction that emitted a claim cost, from its receipt.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ClaimCost {
+    #[serde(rename = "gasUsed")]
+    pub gas_used: u64,
+    #[serde(rename = "effectiveGasPrice")]
+    pub effective_gas_price: u128,
+    /// This claim's share in wei of the transaction fee, which is split evenly between the
+    /// claims the transaction emitted.
+    #[serde(rename = "feePaid")]
+    pub fee_paid: u128,
+}
+
+/// Aggregate claim costs.  Fee fractions divide wei by token base units, so they are only
+/// comparable between tokens with the same decimals and price.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ClaimCostStat {
+    /// The claims with a cost.
+    pub count: u64,
+    pub total_fee: u128,
+    pub fee_per_claim_mean: f64,
+    pub fee_per_claim_min: u128,
+    pub fee_per_claim_max: u128,
+    /// The mean of each claim's fee over its claimed amount, for claims of a nonzero amount.
+    pub fee_fraction_mean: f64,
+    /// The total fee over the total claimed amount.
+    pub fee_fraction_total: f64,
+}
+
+impl ClaimCostStat {
+    /// Aggregate the costs of the claims that have one.
+    pub fn from_claims<'a>(claims: impl IntoIterator<Item = &'a RewardsClaimed>) -> Self {
+        ClaimCostStat::from_fees(
+            claims
+                .into_iter()
+                .filter_map(|claim| Some((claim.claimed_amount, claim.cost.as_ref()?.fee_paid))),
+        )
+    }
+
+    /// Aggregate `(claimed_amount, fee_paid)` pairs, one for each claim with a cost.
+    pub fn from_fees(fees: impl IntoIterator<Item = (u128, u128)>) -> Self {
+        let mut stat = ClaimCostStat {
+            fee_per_claim_min: u128::MAX,
+            ..Default::default()
+        };
+        let mut fractions = 0;
+        let mut fee_fraction_sum = 0.0;
+        let mut total_claimed = 0.0;
+        for (claimed_amount, fee_paid) in fees {
+            stat.count += 1;
+            stat.total_fee = stat.total_fee.saturating_add(fee_paid);
+            stat.fee_per_claim_min = stat.fee_per_claim_min.min(fee_paid);
+            stat.fee_per_claim_max = stat.fee_per_claim_max.max(fee_paid);
+            if claimed_amount > 0 {
+                fractions += 1;
+                fee_fraction_sum += fee_paid as f64 / claimed_amount as f64;
+            }
+            total_claimed += claimed_amount as f64;
+        }
+        if stat.count == 0 {
+            return ClaimCostStat::default();
+        }
+        stat.fee_per_claim_mean = stat.total_fee as f64 / stat.count as f64;
+        if fractions > 0 {
+            stat.fee_fraction_mean = fee_fraction_sum / fractions as f64;
+        }
+        if total_claimed > 0.0 {
+            stat.fee_fraction_total = stat.total_fee as f64 / total_claimed;
+        }
+        stat
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StrategyAndMultiplier {
+    pub strategy: String,
+    pub multiplier: u128,
+}
+
+/// A flattened `AVSRewardsSubmissionCreated` or `RewardsSubmissionForAllCreated` event.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewardsSubmission {
+    /// The AVS, or the submitter for a rewards-for-all submission.
+    pub avs: String,
+    #[serde(rename = "submissionNonce")]
+    pub submission_nonce: u64,
+    #[serde(rename = "rewardsSubmissionHash")]
+    pub rewards_submission_hash: [u8; 32],
+    #[serde(rename = "forAll")]
+    pub for_all: bool,
+    #[serde(rename = "strategiesAndMultipliers")]
+    pub strategies_and_multipliers: Vec<StrategyAndMultiplier>,
+    pub token: String,
+    pub amount: u128,
+    #[serde(rename = "startTimestamp")]
+    pub start_timestamp: u32,
+    pub duration: u32,
 }
 
 #[cfg(test)]
@@ -41,5 +157,140 @@ mod tests {
         assert_eq!(rewards_claimed.recipient, "0x126");
         assert_eq!(rewards_claimed.token, "0x127");
         assert_eq!(rewards_claimed.claimed_amount, 127);
+        assert_eq!(rewards_claimed.block_number, 0);
+        assert_eq!(rewards_claimed.transaction_hash, [0; 32]);
+        assert_eq!(rewards_claimed.log_index, 0);
+    }
+
+    #[test]
+    fn test_rewards_claimed_event_id_deserialization() {
+        let rewards_claimed = r#"{
+            "root": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+            "earner": "0x124",
+            "claimer": "0x125",
+            "recipient": "0x126",
+            "token": "0x127",
+            "claimedAmount": 127,
+            "blockNumber": 1671211,
+            "transactionHash": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7],
+            "logIndex": 3
+        }"#;
+        let rewards_claimed: RewardsClaimed = serde_json::from_str(rewards_claimed).unwrap();
+        assert_eq!(rewards_claimed.block_number, 1671211);
+        assert_eq!(rewards_claimed.transaction_hash[31], 7);
+        assert_eq!(rewards_claimed.log_index, 3);
+    }
+
+    #[test]
+    fn test_rewards_submission_deserialization() {
+        let rewards_submission = r#"{
+            "avs": "0x124",
+            "submissionNonce": 3,
+            "rewardsSubmissionHash": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+            "forAll": false,
+            "strategiesAndMultipliers": [
+                { "strategy": "0x125", "multiplier": 1000000000000000000 },
+                { "strategy": "0x126", "multiplier": 2000000000000000000 }
+            ],
+            "token": "0x127",
+            "amount": 5000,
+            "startTimestamp": 1720656000,
+            "duration": 604800
+        }"#;
+        let rewards_submission: RewardsSubmission =
+            serde_json::from_str(rewards_submission).unwrap();
+        assert_eq!(rewards_submission.avs, "0x124");
+        assert_eq!(rewards_submission.submission_nonce, 3);
+        assert_eq!(rewards_submission.rewards_submission_hash[31], 2);
+        assert!(!rewards_submission.for_all);
+        assert_eq!(rewards_submission.strategies_and_multipliers.len(), 2);
+        assert_eq!(
+            rewards_submission.strategies_and_multipliers[1].strategy,
+            "0x126"
+        );
+        assert_eq!(
+            rewards_submission.strategies_and_multipliers[1].multiplier,
+            2_000_000_000_000_000_000
+        );
+        assert_eq!(rewards_submission.token, "0x127");
+        assert_eq!(rewards_submission.amount, 5000);
+        assert_eq!(rewards_submission.start_timestamp, 1720656000);
+        assert_eq!(rewards_submission.duration, 604800);
+    }
+
+    fn claim(transaction_hash: [u8; 32], log_index: u64, claimed_amount: u128) -> RewardsClaimed {
+        RewardsClaimed {
+            root: [0; 32],
+            earner: "0x124".to_string(),
+            claimer: "0x125".to_string(),
+            recipient: "0x126".to_string(),
+            token: "0x127".to_string(),
+            claimed_amount,
+            block_number: 1,
+            transaction_hash,
+            log_index,
+            cost: None,
+        }
+    }
+
+    fn with_cost(claim: RewardsClaimed, fee_paid: u128) -> RewardsClaimed {
+        RewardsClaimed {
+            cost: Some(ClaimCost {
+                gas_used: 100_000,
+                effective_gas_price: 2,
+                fee_paid,
+            }),
+            ..claim
+        }
+    }
+
+    #[test]
+    fn test_claim_cost_stat() {
+        let claims = vec![
+            with_cost(claim([1; 32], 0, 1_000_000), 200_000),
+            with_cost(claim([2; 32], 0, 400_000), 100_000),
+            with_cost(claim([2; 32], 1, 400_000), 100_000),
+            with_cost(claim([3; 32], 0, 0), 50_000),
+            claim([4; 32], 0, 1),
+        ];
+        let stat = ClaimCostStat::from_claims(&claims);
+        assert_eq!(stat.count, 4);
+        assert_eq!(stat.total_fee, 450_000);
+        assert_eq!(stat.fee_per_claim_min, 50_000);
+        assert_eq!(stat.fee_per_claim_max, 200_000);
+        assert!((stat.fee_per_claim_mean - 450_000.0 / 4.0).abs() < 1e-9);
+        // the zero amount claim has no fraction of its own
+        assert!((stat.fee_fraction_mean - (0.2 + 0.25 + 0.25) / 3.0).abs() < 1e-9);
+        assert!((stat.fee_fraction_total - 450_000.0 / 1_800_000.0).abs() < 1e-9);
+        assert_eq!(
+            ClaimCostStat::from_fees([
+                (1_000_000, 200_000),
+                (400_000, 100_000),
+                (400_000, 100_000),
+                (0, 50_000)
+            ]),
+            stat
+        );
+    }
+
+    #[test]
+    fn test_claim_cost_stat_empty() {
+        let stat = ClaimCostStat::from_claims(&[claim([1; 32], 0, 1)]);
+        assert_eq!(stat, ClaimCostStat::default());
+        assert_eq!(stat.fee_per_claim_min, 0);
+    }
+
+    #[test]
+    fn test_claim_cost_serialization() {
+        let plain = claim([1; 32], 0, 1);
+        let json = serde_json::to_value(&plain).unwrap();
+        assert!(json.get("cost").is_none());
+        let costed = with_cost(plain, 7);
+        let json = serde_json::to_string(&costed).unwrap();
+        assert!(json.contains(r#""cost":{"gasUsed":100000,"effectiveGasPrice":2,"feePaid":7}"#));
+        assert_eq!(
+            serde_json::from_str::<RewardsClaimed>(&json).unwrap(),
+            costed
+        );
     }
 }
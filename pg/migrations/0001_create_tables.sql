@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS rewards_claimed (
+    id SERIAL PRIMARY KEY,
+    root CHAR(64),
+    earner CHAR(42),
+    claimer CHAR(42),
+    recipient CHAR(42),
+    token CHAR(42),
+    claimed_amount NUMERIC
+);
+CREATE INDEX IF NOT EXISTS rewards_claimed_id_index ON rewards_claimed (id, root);
+CREATE INDEX IF NOT EXISTS rewards_claimed_claimed_index ON rewards_claimed (claimed_amount);
+
+CREATE TABLE IF NOT EXISTS rewards_submission (
+    id SERIAL PRIMARY KEY,
+    avs CHAR(42),
+    submission_nonce BIGINT,
+    rewards_submission_hash CHAR(64),
+    for_all BOOLEAN,
+    token CHAR(42),
+    amount NUMERIC,
+    start_timestamp BIGINT,
+    duration BIGINT
+);
+CREATE TABLE IF NOT EXISTS rewards_submission_strategy (
+    id SERIAL PRIMARY KEY,
+    rewards_submission_hash CHAR(64),
+    strategy CHAR(42),
+    multiplier NUMERIC
+);
+CREATE INDEX IF NOT EXISTS rewards_submission_avs_token_index ON rewards_submission (avs, token, start_timestamp);
+CREATE INDEX IF NOT EXISTS rewards_submission_strategy_hash_index ON rewards_submission_strategy (rewards_submission_hash);
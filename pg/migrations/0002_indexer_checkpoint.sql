@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS indexer_checkpoint (
+    id INTEGER PRIMARY KEY,
+    block_number BIGINT NOT NULL
+);
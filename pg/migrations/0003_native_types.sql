@@ -0,0 +1,28 @@
+ALTER TABLE rewards_claimed
+    ALTER COLUMN root TYPE BYTEA USING decode(root, 'hex'),
+    ALTER COLUMN earner TYPE BYTEA USING decode(substr(earner, 3), 'hex'),
+    ALTER COLUMN claimer TYPE BYTEA USING decode(substr(claimer, 3), 'hex'),
+    ALTER COLUMN recipient TYPE BYTEA USING decode(substr(recipient, 3), 'hex'),
+    ALTER COLUMN token TYPE BYTEA USING decode(substr(token, 3), 'hex'),
+    ALTER COLUMN claimed_amount TYPE NUMERIC(78, 0),
+    ADD CONSTRAINT rewards_claimed_root_length CHECK (octet_length(root) = 32),
+    ADD CONSTRAINT rewards_claimed_earner_length CHECK (octet_length(earner) = 20),
+    ADD CONSTRAINT rewards_claimed_claimer_length CHECK (octet_length(claimer) = 20),
+    ADD CONSTRAINT rewards_claimed_recipient_length CHECK (octet_length(recipient) = 20),
+    ADD CONSTRAINT rewards_claimed_token_length CHECK (octet_length(token) = 20);
+
+ALTER TABLE rewards_submission
+    ALTER COLUMN avs TYPE BYTEA USING decode(substr(avs, 3), 'hex'),
+    ALTER COLUMN rewards_submission_hash TYPE BYTEA USING decode(rewards_submission_hash, 'hex'),
+    ALTER COLUMN token TYPE BYTEA USING decode(substr(token, 3), 'hex'),
+    ALTER COLUMN amount TYPE NUMERIC(78, 0),
+    ADD CONSTRAINT rewards_submission_avs_length CHECK (octet_length(avs) = 20),
+    ADD CONSTRAINT rewards_submission_hash_length CHECK (octet_length(rewards_submission_hash) = 32),
+    ADD CONSTRAINT rewards_submission_token_length CHECK (octet_length(token) = 20);
+
+ALTER TABLE rewards_submission_strategy
+    ALTER COLUMN rewards_submission_hash TYPE BYTEA USING decode(rewards_submission_hash, 'hex'),
+    ALTER COLUMN strategy TYPE BYTEA USING decode(substr(strategy, 3), 'hex'),
+    ALTER COLUMN multiplier TYPE NUMERIC(78, 0),
+    ADD CONSTRAINT rewards_submission_strategy_hash_length CHECK (octet_length(rewards_submission_hash) = 32),
+    ADD CONSTRAINT rewards_submission_strategy_strategy_length CHECK (octet_length(strategy) = 20);
@@ -0,0 +1,6 @@
+-- What each claim's transaction cost, when the indexer fetched its receipt.  The fee paid is the
+-- claim's share of the transaction fee, so fees sum to what claiming cost.
+ALTER TABLE rewards_claimed
+    ADD COLUMN gas_used BIGINT,
+    ADD COLUMN effective_gas_price NUMERIC(78, 0),
+    ADD COLUMN fee_paid NUMERIC(78, 0);
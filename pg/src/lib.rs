@@ -0,0 +1,2 @@
+pub mod numeric;
+pub mod postgres;
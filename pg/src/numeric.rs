@@ -0,0 +1,223 @@
+use std::error::Error;
+
+use alloy::primitives::U256;
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+/// Base of the digits in the `NUMERIC` binary format.
+const NBASE: u64 = 10_000;
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+
+/// A 256-bit unsigned integer stored as `NUMERIC`, converted without loss in both directions.
+/// `NUMERIC(78, 0)` holds every value up to `U256::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Numeric(pub U256);
+
+impl From<U256> for Numeric {
+    fn from(value: U256) -> Self {
+        Numeric(value)
+    }
+}
+
+impl From<u128> for Numeric {
+    fn from(value: u128) -> Self {
+        Numeric(U256::from(value))
+    }
+}
+
+impl ToSql for Numeric {
+    /// Write the header `ndigits, weight, sign, dscale` followed by the base 10000 digits, most
+    /// significant first.  Trailing zero digits are implied by the weight and left out.
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let base = U256::from(NBASE);
+        let mut digits = Vec::new();
+        let mut value = self.0;
+        while !value.is_zero() {
+            let (quotient, remainder) = value.div_rem(base);
+            digits.push(remainder.to::<u16>());
+            value = quotient;
+        }
+        let weight = digits.len().saturating_sub(1) as i16;
+        let trailing_zeros = digits.iter().take_while(|digit| **digit == 0).count();
+        let digits = &digits[trailing_zeros..];
+        out.put_i16(digits.len() as i16);
+        out.put_i16(weight);
+        out.put_u16(NUMERIC_POS);
+        out.put_u16(0);
+        for digit in digits.iter().rev() {
+            out.put_u16(*digit);
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Numeric {
+    /// Read a `NUMERIC`, rejecting NaN, infinities, negative and fractional values and values
+    /// over `U256::MAX`.
+    fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() < 8 {
+            return Err("NUMERIC header too short".into());
+        }
+        let ndigits = raw.get_i16();
+        let weight = raw.get_i16();
+        let sign = raw.get_u16();
+        let _dscale = raw.get_u16();
+        if ndigits < 0 || raw.len() != 2 * ndigits as usize {
+            return Err(format!("Invalid NUMERIC digit count: {}", ndigits).into());
+        }
+        let base = U256::from(NBASE);
+        let overflow = || format!("NUMERIC over {} bits", U256::BITS);
+        let mut value = U256::ZERO;
+        for i in 0..ndigits {
+            let digit = raw.get_u16();
+            if digit as u64 >= NBASE {
+                return Err(format!("Invalid NUMERIC digit: {}", digit).into());
+            }
+            if weight < i {
+                if digit != 0 {
+                    return Err("NUMERIC has a fractional part".into());
+                }
+                continue;
+            }
+            value = value
+                .checked_mul(base)
+                .and_then(|value| value.checked_add(U256::from(digit)))
+                .ok_or_else(overflow)?;
+        }
+        // digits between the last stored one and the units are zero
+        for _ in ndigits..=weight {
+            value = value.checked_mul(base).ok_or_else(overflow)?;
+        }
+        match sign {
+            NUMERIC_POS => Ok(Numeric(value)),
+            NUMERIC_NEG if value.is_zero() => Ok(Numeric(value)),
+            NUMERIC_NEG => Err("NUMERIC is negative".into()),
+            _ => Err("NUMERIC is NaN or infinite".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: U256) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        Numeric(value).to_sql(&Type::NUMERIC, &mut out).unwrap();
+        out.to_vec()
+    }
+
+    fn decode(raw: &[u8]) -> Result<U256, Box<dyn Error + Sync + Send>> {
+        Numeric::from_sql(&Type::NUMERIC, raw).map(|numeric| numeric.0)
+    }
+
+    /// A `NUMERIC` in the binary format, as the server would send it.
+    fn raw(weight: i16, sign: u16, dscale: u16, digits: &[u16]) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        out.put_i16(digits.len() as i16);
+        out.put_i16(weight);
+        out.put_u16(sign);
+        out.put_u16(dscale);
+        for digit in digits {
+            out.put_u16(*digit);
+        }
+        out.to_vec()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = [
+            U256::ZERO,
+            U256::from(1),
+            U256::from(9999),
+            U256::from(10000),
+            U256::from(10u128.pow(18)),
+            U256::from(1_2345_0000_0000u128),
+            U256::from(10u128.pow(38)),
+            U256::from(u128::MAX),
+            U256::MAX,
+        ];
+        for value in values {
+            assert_eq!(decode(&encode(value)).unwrap(), value, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(encode(U256::ZERO), raw(0, NUMERIC_POS, 0, &[]));
+        assert_eq!(encode(U256::from(1)), raw(0, NUMERIC_POS, 0, &[1]));
+        assert_eq!(encode(U256::from(9999)), raw(0, NUMERIC_POS, 0, &[9999]));
+        // trailing zero digit groups are implied by the weight
+        assert_eq!(encode(U256::from(10000)), raw(1, NUMERIC_POS, 0, &[1]));
+        assert_eq!(
+            encode(U256::from(10u128.pow(18))),
+            raw(4, NUMERIC_POS, 0, &[100])
+        );
+        assert_eq!(
+            encode(U256::from(1_2345_0000_0000u128)),
+            raw(3, NUMERIC_POS, 0, &[1, 2345])
+        );
+        assert_eq!(
+            encode(U256::MAX),
+            raw(
+                19,
+                NUMERIC_POS,
+                0,
+                &[
+                    11, 5792, 892, 3731, 6195, 4235, 7098, 5008, 6879, 785, 3269, 9846, 6564, 564,
+                    394, 5758, 4007, 9131, 2963, 9935
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_sql_accepts_server_encodings() {
+        // explicit trailing zero digits, and a zero fraction at a nonzero scale
+        assert_eq!(
+            decode(&raw(1, NUMERIC_POS, 0, &[1, 0])).unwrap(),
+            U256::from(10000)
+        );
+        assert_eq!(
+            decode(&raw(0, NUMERIC_POS, 4, &[7, 0])).unwrap(),
+            U256::from(7)
+        );
+        assert_eq!(decode(&raw(0, NUMERIC_NEG, 0, &[])).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_from_sql_rejects() {
+        // -5
+        assert!(decode(&raw(0, NUMERIC_NEG, 0, &[5])).is_err());
+        // 1.5
+        assert!(decode(&raw(0, NUMERIC_POS, 4, &[1, 5000])).is_err());
+        // 0.0001
+        assert!(decode(&raw(-1, NUMERIC_POS, 4, &[1])).is_err());
+        // NaN and +infinity
+        assert!(decode(&raw(0, 0xC000, 0, &[])).is_err());
+        assert!(decode(&raw(0, 0xD000, 0, &[])).is_err());
+        // 10^80 and U256::MAX + 1 are over 256 bits
+        assert!(decode(&raw(20, NUMERIC_POS, 0, &[1])).is_err());
+        let mut too_large = encode(U256::MAX);
+        let last = too_large.len() - 1;
+        too_large[last] += 1;
+        assert!(decode(&too_large).is_err());
+        // a digit out of base, a short header and a digit count that does not match the data
+        assert!(decode(&raw(0, NUMERIC_POS, 0, &[10000])).is_err());
+        assert!(decode(&[0, 0, 0, 0]).is_err());
+        let mut truncated = raw(1, NUMERIC_POS, 0, &[1, 2]);
+        truncated.truncate(truncated.len() - 2);
+        assert!(decode(&truncated).is_err());
+    }
+}
@@ -1,17 +1,34 @@
-use tokio_postgres::{Client, Error, NoTls};
+use std::{collections::BTreeSet, fmt, pin::pin, str::FromStr};
+
+use futures_util::future::try_join_all;
+use tokio_postgres::{
+    binary_copy::BinaryCopyInWriter,
+    types::{ToSql, Type},
+    Client, Error, GenericClient, NoTls, Transaction,
+};
 use tracing::warn;
 
-use rust_decimal::prelude::*;
-use rust_decimal::Decimal;
+use alloy::primitives::{Address, U256};
 
-#[derive(Debug)]
+use crate::numeric::Numeric;
+
+#[derive(Debug, Default)]
 #[allow(dead_code)]
 pub struct ClaimedAmountStat {
-    pub sum: Decimal,
+    pub sum: U256,
     pub count: u64,
     pub claimed_mean: f64,
-    pub claimed_max: Decimal,
-    pub claimed_min: Decimal,
+    pub claimed_max: U256,
+    pub claimed_min: U256,
+}
+
+/// Total rewards offered by an AVS in a token over a time window.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct RewardsOffered {
+    pub avs: String,
+    pub token: String,
+    pub total: U256,
 }
 
 /// A wrapper around `tokio_postgres::Client` for working with PostgreSQL database.
@@ -29,117 +46,701 @@ pub async fn init_postgres(connection_string: &str) -> eyre::Result<PostgresClie
     Ok(PostgresClient { client })
 }
 
-fn array_to_hex_string(array: &[u8; 32]) -> String {
-    let mut hex_string = String::new();
-    for byte in array {
-        hex_string.push_str(&format!("{:02x}", byte));
+/// An address as the 20 bytes stored in a `BYTEA` column, or `None` if it does not parse.
+fn address_bytes(address: &str) -> Option<Vec<u8>> {
+    match Address::from_str(address) {
+        Ok(address) => Some(address.to_vec()),
+        Err(e) => {
+            warn!("Invalid address {:?}: {}", address, e);
+            None
+        }
     }
-    hex_string
 }
 
-pub async fn create_tables(postgres: &mut PostgresClient) -> Result<(), Error> {
-    postgres
-        .client
-        .execute("DROP INDEX IF EXISTS rewards_claimed_id_index", &[])
-        .await?;
-    postgres
-        .client
-        .execute("DROP INDEX IF EXISTS rewards_claimed_claimed_index", &[])
-        .await?;
-    postgres
-        .client
-        .execute("DROP TABLE IF EXISTS rewards_claimed", &[])
-        .await?;
+/// The checksummed address in a `BYTEA` column, or an error if it is not 20 bytes.
+fn address_from_bytes(bytes: &[u8]) -> eyre::Result<String> {
+    let address = Address::try_from(bytes)
+        .map_err(|_| eyre::eyre!("Address is {} bytes, not 20", bytes.len()))?;
+    Ok(address.to_checksum(None))
+}
+
+/// A schema change, applied once and in version order.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// The schema as versioned changes.  The first only creates what is missing, so it adopts a
+/// database created before migrations were tracked.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_tables",
+        sql: include_str!("../migrations/0001_create_tables.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "indexer_checkpoint",
+        sql: include_str!("../migrations/0002_indexer_checkpoint.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "native_types",
+        sql: include_str!("../migrations/0003_native_types.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "event_identity",
+        sql: include_str!("../migrations/0004_event_identity.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "claim_costs",
+        sql: include_str!("../migrations/0005_claim_costs.sql"),
+    },
+];
+
+/// Whether a migration has been applied to the database.
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied: bool,
+}
+
+async fn applied_migrations(postgres: &mut PostgresClient) -> Result<BTreeSet<i64>, Error> {
     postgres
         .client
         .execute(
-            "CREATE TABLE IF NOT EXISTS rewards_claimed (
-                id SERIAL PRIMARY KEY,
-                root CHAR(64),
-                earner CHAR(42),
-                claimer CHAR(42),
-                recipient CHAR(42),
-                token CHAR(42),
-                claimed_amount NUMERIC
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
             )",
             &[],
         )
         .await?;
-    postgres
+    let rows = postgres
         .client
-        .execute(
-            "CREATE INDEX IF NOT EXISTS rewards_claimed_id_index ON rewards_claimed (id, root)",
-            &[],
-        )
+        .query("SELECT version FROM schema_migrations", &[])
         .await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// Apply the migrations not yet recorded in `schema_migrations`, each in its own transaction,
+/// and return the versions applied.  Existing rows are kept.
+pub async fn migrate_up(postgres: &mut PostgresClient) -> Result<Vec<i64>, Error> {
+    let applied = applied_migrations(postgres).await?;
+    let mut versions = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+        let transaction = postgres.client.transaction().await?;
+        transaction.batch_execute(migration.sql).await?;
+        transaction
+            .execute(
+                "INSERT INTO schema_migrations (version, name) VALUES ($1, $2)",
+                &[&migration.version, &migration.name],
+            )
+            .await?;
+        transaction.commit().await?;
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+pub async fn migration_status(
+    postgres: &mut PostgresClient,
+) -> Result<Vec<MigrationStatus>, Error> {
+    let applied = applied_migrations(postgres).await?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied: applied.contains(&migration.version),
+        })
+        .collect())
+}
+
+const REWARDS_CLAIMED_TABLE: &str = "rewards_claimed";
+/// The scratch table `--benchmark` loads into, so the benchmark never touches the claims.
+const BENCHMARK_TABLE: &str = "rewards_claimed_benchmark";
+const REWARDS_CLAIMED_COLUMN_NAMES: &str =
+    "root, earner, claimer, recipient, token, claimed_amount, block_number, transaction_hash, log_index, gas_used, effective_gas_price, fee_paid";
+const REWARDS_CLAIMED_COLUMNS: usize = 12;
+/// A claim already written is skipped, so rerunning over the same blocks inserts nothing twice.
+const ON_CLAIM_CONFLICT: &str = "ON CONFLICT (transaction_hash, log_index) DO NOTHING";
+/// The most bind parameters the protocol allows in one statement.
+const MAX_PARAMETERS: usize = u16::MAX as usize;
+
+/// How [`load_rewards_claimed`] writes the claims.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WriteStrategy {
+    /// One unprepared `INSERT` round trip per row, outside a transaction.
+    Insert,
+    /// One `INSERT` prepared once and executed per row.
+    Prepared,
+    /// The prepared `INSERT` with up to `batch_size` executions in flight at once, pipelined
+    /// over the one connection.
+    Pipelined,
+    /// Multi-row `INSERT ... VALUES (...), (...)` statements of `batch_size` rows.
+    Values,
+    /// `COPY ... FROM STDIN BINARY`.
+    Copy,
+}
+
+impl WriteStrategy {
+    pub const ALL: [WriteStrategy; 5] = [
+        WriteStrategy::Insert,
+        WriteStrategy::Prepared,
+        WriteStrategy::Pipelined,
+        WriteStrategy::Values,
+        WriteStrategy::Copy,
+    ];
+
+    /// Whether the strategy depends on the batch size.
+    pub fn batched(&self) -> bool {
+        matches!(self, WriteStrategy::Pipelined | WriteStrategy::Values)
+    }
+
+    /// The largest batch size the strategy can write.  A values statement binds every column
+    /// of every row and must stay under the protocol's parameter limit.
+    pub fn max_batch_size(&self) -> usize {
+        match self {
+            WriteStrategy::Values => MAX_PARAMETERS / REWARDS_CLAIMED_COLUMNS,
+            _ => usize::MAX,
+        }
+    }
+}
+
+impl FromStr for WriteStrategy {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s {
+            "insert" => Ok(WriteStrategy::Insert),
+            "prepared" => Ok(WriteStrategy::Prepared),
+            "pipelined" => Ok(WriteStrategy::Pipelined),
+            "values" => Ok(WriteStrategy::Values),
+            "copy" => Ok(WriteStrategy::Copy),
+            _ => Err(eyre::eyre!("Unknown write strategy: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for WriteStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            WriteStrategy::Insert => "insert",
+            WriteStrategy::Prepared => "prepared",
+            WriteStrategy::Pipelined => "pipelined",
+            WriteStrategy::Values => "values",
+            WriteStrategy::Copy => "copy",
+        };
+        f.pad(name)
+    }
+}
+
+/// A claim as `rewards_claimed` column values: the root and addresses as raw bytes and the
+/// amount as an exact `NUMERIC`.  A claim without its log has no identity, so it never
+/// conflicts.
+struct ClaimRow<'a> {
+    root: &'a [u8],
+    earner: Vec<u8>,
+    claimer: Vec<u8>,
+    recipient: Vec<u8>,
+    token: Vec<u8>,
+    claimed_amount: Numeric,
+    block_number: Option<i64>,
+    transaction_hash: Option<&'a [u8]>,
+    log_index: Option<i64>,
+    gas_used: Option<i64>,
+    effective_gas_price: Option<Numeric>,
+    fee_paid: Option<Numeric>,
+}
+
+impl ClaimRow<'_> {
+    fn params(&self) -> [&(dyn ToSql + Sync); REWARDS_CLAIMED_COLUMNS] {
+        [
+            &self.root,
+            &self.earner,
+            &self.claimer,
+            &self.recipient,
+            &self.token,
+            &self.claimed_amount,
+            &self.block_number,
+            &self.transaction_hash,
+            &self.log_index,
+            &self.gas_used,
+            &self.effective_gas_price,
+            &self.fee_paid,
+        ]
+    }
+}
+
+/// The claims to write, skipping any with an address that does not parse.
+fn claim_rows(rewards_claimed: &[eigen_types::RewardsClaimed]) -> Vec<ClaimRow<'_>> {
+    rewards_claimed
+        .iter()
+        .filter_map(|reward| {
+            let event = reward.has_event_id();
+            Some(ClaimRow {
+                root: &reward.root,
+                earner: address_bytes(&reward.earner)?,
+                claimer: address_bytes(&reward.claimer)?,
+                recipient: address_bytes(&reward.recipient)?,
+                token: address_bytes(&reward.token)?,
+                claimed_amount: reward.claimed_amount.into(),
+                block_number: event.then_some(reward.block_number as i64),
+                transaction_hash: event.then_some(&reward.transaction_hash[..]),
+                log_index: event.then_some(reward.log_index as i64),
+                gas_used: reward.cost.as_ref().map(|cost| cost.gas_used as i64),
+                effective_gas_price: reward
+                    .cost
+                    .as_ref()
+                    .map(|cost| cost.effective_gas_price.into()),
+                fee_paid: reward.cost.as_ref().map(|cost| cost.fee_paid.into()),
+            })
+        })
+        .collect()
+}
+
+pub async fn write_rewards_claimed(
+    postgres: &mut PostgresClient,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> Result<(), Error> {
+    insert_rewards_claimed(&postgres.client, REWARDS_CLAIMED_TABLE, rewards_claimed).await?;
+    Ok(())
+}
+
+/// Write the claims with `strategy`, returning the number of rows written.  Every strategy but
+/// [`WriteStrategy::Insert`] writes in one transaction, so a failed load leaves the table
+/// unchanged.  `batch_size` only applies to the [`WriteStrategy::batched`] strategies.
+pub async fn load_rewards_claimed(
+    postgres: &mut PostgresClient,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    strategy: WriteStrategy,
+    batch_size: usize,
+) -> eyre::Result<u64> {
+    load_into(
+        postgres,
+        REWARDS_CLAIMED_TABLE,
+        rewards_claimed,
+        strategy,
+        batch_size,
+    )
+    .await
+}
+
+/// Load the claims like [`load_rewards_claimed`], but into an empty temporary copy of the
+/// claims table, indexes included, recreated on every call.  The claims table is not touched.
+pub async fn benchmark_rewards_claimed(
+    postgres: &mut PostgresClient,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    strategy: WriteStrategy,
+    batch_size: usize,
+) -> eyre::Result<u64> {
+    // the copied id default would draw from the claims sequence, so give the copy its own
     postgres
         .client
+        .batch_execute(&format!(
+            "DROP TABLE IF EXISTS pg_temp.{benchmark};
+            CREATE TEMP TABLE {benchmark} (LIKE {claims} INCLUDING ALL EXCLUDING DEFAULTS);
+            ALTER TABLE {benchmark} ALTER COLUMN id ADD GENERATED BY DEFAULT AS IDENTITY;",
+            benchmark = BENCHMARK_TABLE,
+            claims = REWARDS_CLAIMED_TABLE,
+        ))
+        .await?;
+    load_into(
+        postgres,
+        BENCHMARK_TABLE,
+        rewards_claimed,
+        strategy,
+        batch_size,
+    )
+    .await
+}
+
+async fn load_into(
+    postgres: &mut PostgresClient,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    strategy: WriteStrategy,
+    batch_size: usize,
+) -> eyre::Result<u64> {
+    if strategy.batched() && batch_size == 0 {
+        return Err(eyre::eyre!("batch size must be positive"));
+    }
+    if batch_size > strategy.max_batch_size() {
+        return Err(eyre::eyre!(
+            "batch size must be at most {} for {}",
+            strategy.max_batch_size(),
+            strategy
+        ));
+    }
+    if strategy == WriteStrategy::Insert {
+        return Ok(insert_rewards_claimed(&postgres.client, table, rewards_claimed).await?);
+    }
+    let transaction = postgres.client.transaction().await?;
+    let rows = match strategy {
+        WriteStrategy::Insert => unreachable!("insert is written outside a transaction"),
+        WriteStrategy::Prepared => {
+            prepared_rewards_claimed(&transaction, table, rewards_claimed).await?
+        }
+        WriteStrategy::Pipelined => {
+            pipelined_rewards_claimed(&transaction, table, rewards_claimed, batch_size).await?
+        }
+        WriteStrategy::Values => {
+            values_rewards_claimed(&transaction, table, rewards_claimed, batch_size).await?
+        }
+        WriteStrategy::Copy => {
+            copy_rewards_claimed_in(&transaction, table, rewards_claimed).await?
+        }
+    };
+    transaction.commit().await?;
+    Ok(rows)
+}
+
+async fn insert_rewards_claimed<C: GenericClient>(
+    client: &C,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> Result<u64, Error> {
+    let statement = values_statement(table, 1);
+    let mut rows = 0;
+    for row in claim_rows(rewards_claimed) {
+        rows += client.execute(statement.as_str(), &row.params()).await?;
+    }
+    Ok(rows)
+}
+
+async fn prepared_rewards_claimed(
+    transaction: &Transaction<'_>,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> Result<u64, Error> {
+    let statement = transaction.prepare(&values_statement(table, 1)).await?;
+    let mut rows = 0;
+    for row in claim_rows(rewards_claimed) {
+        rows += transaction.execute(&statement, &row.params()).await?;
+    }
+    Ok(rows)
+}
+
+/// Issue up to `depth` executions before awaiting any, so the connection sends them back to
+/// back instead of waiting a round trip for each.
+async fn pipelined_rewards_claimed(
+    transaction: &Transaction<'_>,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    depth: usize,
+) -> Result<u64, Error> {
+    let statement = transaction.prepare(&values_statement(table, 1)).await?;
+    let claim_rows = claim_rows(rewards_claimed);
+    let mut rows = 0;
+    for chunk in claim_rows.chunks(depth) {
+        let executions = chunk
+            .iter()
+            .map(|row| transaction.execute_raw(&statement, row.params()));
+        rows += try_join_all(executions).await?.iter().sum::<u64>();
+    }
+    Ok(rows)
+}
+
+async fn values_rewards_claimed(
+    transaction: &Transaction<'_>,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    batch_size: usize,
+) -> Result<u64, Error> {
+    let claim_rows = claim_rows(rewards_claimed);
+    let mut rows = 0;
+    let mut chunks = claim_rows.chunks_exact(batch_size);
+    if chunks.len() > 0 {
+        // every full batch shares one prepared statement
+        let statement = transaction
+            .prepare(&values_statement(table, batch_size))
+            .await?;
+        for chunk in &mut chunks {
+            rows += transaction
+                .execute(&statement, &values_params(chunk))
+                .await?;
+        }
+    }
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        rows += transaction
+            .execute(
+                values_statement(table, remainder.len()).as_str(),
+                &values_params(remainder),
+            )
+            .await?;
+    }
+    Ok(rows)
+}
+
+fn values_params<'a>(chunk: &'a [ClaimRow]) -> Vec<&'a (dyn ToSql + Sync)> {
+    chunk.iter().flat_map(ClaimRow::params).collect()
+}
+
+/// `INSERT ... VALUES ($1, ..., $12), ($13, ..., $24), ... ON CONFLICT ...` for `rows` rows.
+fn values_statement(table: &str, rows: usize) -> String {
+    let values = (0..rows)
+        .map(|row| {
+            let params = (1..=REWARDS_CLAIMED_COLUMNS)
+                .map(|column| format!("${}", row * REWARDS_CLAIMED_COLUMNS + column))
+                .collect::<Vec<_>>();
+            format!("({})", params.join(", "))
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "INSERT INTO {} ({}) VALUES {} {}",
+        table,
+        REWARDS_CLAIMED_COLUMN_NAMES,
+        values.join(", "),
+        ON_CLAIM_CONFLICT
+    )
+}
+
+/// `COPY` cannot skip conflicting rows, so the claims are copied into a staging table dropped
+/// at commit and moved over with one `INSERT ... SELECT`.
+async fn copy_rewards_claimed_in(
+    transaction: &Transaction<'_>,
+    table: &str,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> Result<u64, Error> {
+    transaction
+        .batch_execute(&format!(
+            "CREATE TEMP TABLE rewards_claimed_copy ON COMMIT DROP AS
+                SELECT {} FROM {} WITH NO DATA",
+            REWARDS_CLAIMED_COLUMN_NAMES, table
+        ))
+        .await?;
+    let sink = transaction
+        .copy_in(&format!(
+            "COPY rewards_claimed_copy ({}) FROM STDIN BINARY",
+            REWARDS_CLAIMED_COLUMN_NAMES
+        ))
+        .await?;
+    let mut writer = pin!(BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::BYTEA,
+            Type::BYTEA,
+            Type::BYTEA,
+            Type::BYTEA,
+            Type::BYTEA,
+            Type::NUMERIC,
+            Type::INT8,
+            Type::BYTEA,
+            Type::INT8,
+            Type::INT8,
+            Type::NUMERIC,
+            Type::NUMERIC,
+        ],
+    ));
+    for row in claim_rows(rewards_claimed) {
+        writer.as_mut().write(&row.params()).await?;
+    }
+    writer.finish().await?;
+    transaction
         .execute(
-            "CREATE INDEX IF NOT EXISTS rewards_claimed_claimed_index ON rewards_claimed (claimed_amount)",
+            &format!(
+                "INSERT INTO {table} ({columns}) SELECT {columns} FROM rewards_claimed_copy {}",
+                ON_CLAIM_CONFLICT,
+                table = table,
+                columns = REWARDS_CLAIMED_COLUMN_NAMES,
+            ),
             &[],
         )
-        .await?;
-    Ok(())
+        .await
 }
 
-pub async fn write_rewards_claimed(
+pub async fn write_rewards_submissions(
     postgres: &mut PostgresClient,
-    rewards_claimed: &Vec<eigen_types::RewardsClaimed>,
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+) -> Result<(), Error> {
+    insert_rewards_submissions(&postgres.client, rewards_submissions).await
+}
+
+async fn insert_rewards_submissions<C: GenericClient>(
+    client: &C,
+    rewards_submissions: &[eigen_types::RewardsSubmission],
 ) -> Result<(), Error> {
-    for reward in rewards_claimed {
-        if reward.claimed_amount > 5000u128 * 10u128.pow(18) {
-            warn!("Claimed amount too high: {:?}", reward.claimed_amount);
+    for submission in rewards_submissions {
+        let (Some(avs), Some(token)) = (
+            address_bytes(&submission.avs),
+            address_bytes(&submission.token),
+        ) else {
             continue;
-        }
-        let claimed_amount: Decimal = reward.claimed_amount.into();
-        postgres
-            .client
+        };
+        let amount = Numeric::from(submission.amount);
+        let rewards_submission_hash = &submission.rewards_submission_hash[..];
+        let inserted = client
             .execute(
-                "INSERT INTO rewards_claimed (root, earner, claimer, recipient, token, claimed_amount) VALUES ($1, $2, $3, $4, $5, $6)",
+                "INSERT INTO rewards_submission (avs, submission_nonce, rewards_submission_hash, for_all, token, amount, start_timestamp, duration) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (rewards_submission_hash) DO NOTHING",
                 &[
-                    &array_to_hex_string(&reward.root),
-                    &reward.earner,
-                    &reward.claimer,
-                    &reward.recipient,
-                    &reward.token,
-                    &claimed_amount,
+                    &avs,
+                    &(submission.submission_nonce as i64),
+                    &rewards_submission_hash,
+                    &submission.for_all,
+                    &token,
+                    &amount,
+                    &(submission.start_timestamp as i64),
+                    &(submission.duration as i64),
                 ],
             )
             .await?;
+        if inserted == 0 {
+            // already written, along with its strategies
+            continue;
+        }
+        for strategy in &submission.strategies_and_multipliers {
+            let Some(strategy_address) = address_bytes(&strategy.strategy) else {
+                continue;
+            };
+            let multiplier = Numeric::from(strategy.multiplier);
+            client
+                .execute(
+                    "INSERT INTO rewards_submission_strategy (rewards_submission_hash, strategy, multiplier) VALUES ($1, $2, $3)",
+                    &[&rewards_submission_hash, &strategy_address, &multiplier],
+                )
+                .await?;
+        }
     }
     Ok(())
 }
 
-pub async fn read_rewards_claimed_stat(
-    postgres: &mut PostgresClient,
-) -> eyre::Result<ClaimedAmountStat> {
+/// The last block committed by the indexer, if any.  Needs the migrations applied.
+pub async fn read_checkpoint(postgres: &mut PostgresClient) -> eyre::Result<Option<u64>> {
     let rows = postgres
         .client
-        .query("SELECT SUM(claimed_amount) FROM rewards_claimed", &[])
+        .query(
+            "SELECT block_number FROM indexer_checkpoint WHERE id = 1",
+            &[],
+        )
         .await?;
-    let sum = rows[0].get::<usize, Decimal>(0);
-    let rows = postgres
-        .client
-        .query("SELECT COUNT(claimed_amount) FROM rewards_claimed", &[])
+    match rows.first() {
+        Some(row) => Ok(Some(row.try_get::<_, i64>(0)? as u64)),
+        None => Ok(None),
+    }
+}
+
+/// Write a batch of indexed events and advance the checkpoint to `block_number` in one transaction.
+pub async fn write_batch(
+    postgres: &mut PostgresClient,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+    block_number: u64,
+) -> Result<(), Error> {
+    let transaction = postgres.client.transaction().await?;
+    insert_rewards_claimed(&transaction, REWARDS_CLAIMED_TABLE, rewards_claimed).await?;
+    insert_rewards_submissions(&transaction, rewards_submissions).await?;
+    transaction
+        .execute(
+            "INSERT INTO indexer_checkpoint (id, block_number) VALUES (1, $1)
+                ON CONFLICT (id) DO UPDATE SET block_number = EXCLUDED.block_number",
+            &[&(block_number as i64)],
+        )
         .await?;
-    let count = rows[0].get::<usize, i64>(0) as u64;
+    transaction.commit().await
+}
+
+/// Total rewards offered per AVS and token for submissions starting in `[from_timestamp, to_timestamp)`.
+pub async fn read_rewards_offered(
+    postgres: &mut PostgresClient,
+    from_timestamp: u32,
+    to_timestamp: u32,
+) -> eyre::Result<Vec<RewardsOffered>> {
     let rows = postgres
         .client
-        .query("SELECT MAX(claimed_amount) FROM rewards_claimed", &[])
+        .query(
+            "SELECT avs, token, SUM(amount) FROM rewards_submission
+                WHERE start_timestamp >= $1 AND start_timestamp < $2
+                GROUP BY avs, token
+                ORDER BY avs, token",
+            &[&(from_timestamp as i64), &(to_timestamp as i64)],
+        )
         .await?;
-    let claimed_max = rows[0].get::<usize, Decimal>(0);
-    let rows = postgres
+    rows.iter()
+        .map(|row| {
+            Ok(RewardsOffered {
+                avs: address_from_bytes(row.try_get(0)?)?,
+                token: address_from_bytes(row.try_get(1)?)?,
+                // NULL when every amount in the group is NULL
+                total: row.try_get::<_, Option<Numeric>>(2)?.unwrap_or_default().0,
+            })
+        })
+        .collect()
+}
+
+pub async fn read_rewards_claimed_stat(
+    postgres: &mut PostgresClient,
+) -> eyre::Result<ClaimedAmountStat> {
+    let row = postgres
         .client
-        .query("SELECT MIN(claimed_amount) FROM rewards_claimed", &[])
+        .query_one(
+            "SELECT SUM(claimed_amount), COUNT(claimed_amount), MAX(claimed_amount), MIN(claimed_amount)
+                FROM rewards_claimed",
+            &[],
+        )
         .await?;
-    let claimed_min = rows[0].get::<usize, Decimal>(0);
-    let claimed_mean = sum.to_f64().unwrap() / count as f64;
+    let count = row.try_get::<_, i64>(1)? as u64;
+    // the aggregates other than the count are NULL when there are no amounts
+    let (Some(sum), Some(claimed_max), Some(claimed_min)) = (
+        row.try_get::<_, Option<Numeric>>(0)?,
+        row.try_get::<_, Option<Numeric>>(2)?,
+        row.try_get::<_, Option<Numeric>>(3)?,
+    ) else {
+        return Ok(ClaimedAmountStat::default());
+    };
     Ok(ClaimedAmountStat {
-        sum,
+        sum: sum.0,
+        count,
+        claimed_mean: f64::from(sum.0) / count as f64,
+        claimed_max: claimed_max.0,
+        claimed_min: claimed_min.0,
+    })
+}
+
+/// Fee per claim and fee over claimed value, over the claims with a cost.
+pub async fn read_claim_cost_stat(
+    postgres: &mut PostgresClient,
+) -> eyre::Result<eigen_types::ClaimCostStat> {
+    let row = postgres
+        .client
+        .query_one(
+            "SELECT COUNT(fee_paid), SUM(fee_paid), MIN(fee_paid), MAX(fee_paid),
+                    AVG(fee_paid::float8 / NULLIF(claimed_amount, 0)::float8),
+                    SUM(fee_paid)::float8 / NULLIF(SUM(claimed_amount), 0)::float8
+                FROM rewards_claimed
+                WHERE claimed_amount IS NOT NULL AND fee_paid IS NOT NULL",
+            &[],
+        )
+        .await?;
+    let count = row.try_get::<_, i64>(0)? as u64;
+    let fee = |index| -> eyre::Result<u128> {
+        let fee = row
+            .try_get::<_, Option<Numeric>>(index)?
+            .unwrap_or_default()
+            .0;
+        u128::try_from(fee).map_err(|_| eyre::eyre!("Fee out of range: {}", fee))
+    };
+    if count == 0 {
+        return Ok(eigen_types::ClaimCostStat::default());
+    }
+    let total_fee = fee(1)?;
+    Ok(eigen_types::ClaimCostStat {
         count,
-        claimed_mean,
-        claimed_max,
-        claimed_min,
+        total_fee,
+        fee_per_claim_mean: total_fee as f64 / count as f64,
+        fee_per_claim_min: fee(2)?,
+        fee_per_claim_max: fee(3)?,
+        fee_fraction_mean: row.try_get::<_, Option<f64>>(4)?.unwrap_or_default(),
+        fee_fraction_total: row.try_get::<_, Option<f64>>(5)?.unwrap_or_default(),
     })
 }
 
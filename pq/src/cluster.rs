@@ -0,0 +1,186 @@
+use alloy::primitives::U256;
+
+use crate::config::Config;
+use crate::parquet::parse_address;
+
+/// Interleave the bits of `a` and `b`, most significant first, so that ordering by the result
+/// walks the Z-order curve over `(a, b)`.
+pub fn z_order(a: &[u8; 20], b: &[u8; 20]) -> [u8; 40] {
+    let mut interleaved = [0u8; 40];
+    for bit in 0..160 {
+        let (byte, shift) = (bit / 8, 7 - bit % 8);
+        let out = 2 * bit;
+        interleaved[out / 8] |= ((a[byte] >> shift) & 1) << (7 - out % 8);
+        interleaved[(out + 1) / 8] |= ((b[byte] >> shift) & 1) << (7 - (out + 1) % 8);
+    }
+    interleaved
+}
+
+/// `column` of `claim` as bytes that order like the stored value: addresses and hashes as
+/// binary, amounts and integers big-endian.
+fn column_key(claim: &eigen_types::RewardsClaimed, column: &str) -> eyre::Result<Vec<u8>> {
+    Ok(match column {
+        "root" => claim.root.to_vec(),
+        "earner" => parse_address(&claim.earner)?.to_vec(),
+        "claimer" => parse_address(&claim.claimer)?.to_vec(),
+        "recipient" => parse_address(&claim.recipient)?.to_vec(),
+        "token" => parse_address(&claim.token)?.to_vec(),
+        "claimedAmount" => U256::from(claim.claimed_amount)
+            .to_be_bytes::<32>()
+            .to_vec(),
+        "blockNumber" => claim.block_number.to_be_bytes().to_vec(),
+        "transactionHash" => claim.transaction_hash.to_vec(),
+        "logIndex" => claim.log_index.to_be_bytes().to_vec(),
+        _ => return Err(eyre::eyre!("No column named {}", column)),
+    })
+}
+
+/// The key `claim` is ordered by under `config`: the Z-order value of `(token, earner)` when
+/// `zOrder` is set, followed by the `sortKeys` columns.
+fn sort_key(claim: &eigen_types::RewardsClaimed, config: &Config) -> eyre::Result<Vec<u8>> {
+    let mut key = Vec::new();
+    if config.z_order {
+        let token = parse_address(&claim.token)?;
+        let earner = parse_address(&claim.earner)?;
+        key.extend_from_slice(&z_order(&token.0 .0, &earner.0 .0));
+    }
+    for column in &config.sort_keys {
+        key.extend(column_key(claim, column)?);
+    }
+    Ok(key)
+}
+
+/// Reorder `claims` for writing under `config`.  The sort is stable, so claims with equal keys
+/// keep their arrival order.
+pub fn cluster(
+    claims: Vec<eigen_types::RewardsClaimed>,
+    config: &Config,
+) -> eyre::Result<Vec<eigen_types::RewardsClaimed>> {
+    if !config.clustered() {
+        return Ok(claims);
+    }
+    let mut keyed = claims
+        .into_iter()
+        .map(|claim| Ok((sort_key(&claim, config)?, claim)))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(keyed.into_iter().map(|(_, claim)| claim).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parquet::{read_reward_file, ClaimsWriter};
+    use crate::query;
+    use alloy::primitives::Address;
+
+    fn address(i: u64) -> String {
+        Address::left_padding_from(&i.to_be_bytes()).to_checksum(None)
+    }
+
+    /// Claims arriving in block order, cycling through 64 earners and 8 tokens.
+    fn claims(rows: u64) -> Vec<eigen_types::RewardsClaimed> {
+        (0..rows)
+            .map(|i| eigen_types::RewardsClaimed {
+                root: [1; 32],
+                earner: address(1_000 + i % 64),
+                claimer: address(1),
+                recipient: address(1),
+                token: address(100 + i % 8),
+                claimed_amount: i as u128,
+                block_number: i,
+                transaction_hash: [0; 32],
+                log_index: 0,
+                cost: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_z_order_interleaves_bits() {
+        let mut a = [0u8; 20];
+        let mut b = [0u8; 20];
+        a[0] = 0b1000_0000;
+        b[0] = 0b1100_0000;
+        let z = z_order(&a, &b);
+        assert_eq!(z[0], 0b1101_0000);
+        assert!(z[1..].iter().all(|byte| *byte == 0));
+
+        a[19] = 1;
+        let z = z_order(&a, &b);
+        assert_eq!(z[39], 0b0000_0010);
+
+        // a larger high bit in either input dominates the lower bits of both
+        let low = z_order(&[0xff; 20], &[0; 20]);
+        let mut high_b = [0u8; 20];
+        high_b[0] = 0b1000_0000;
+        assert!(z_order(&[0; 20], &high_b) < low);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let config = Config {
+            sort_keys: vec!["token".to_string(), "blockNumber".to_string()],
+            ..Default::default()
+        };
+        let sorted = cluster(claims(32), &config).unwrap();
+        let keys = sorted
+            .iter()
+            .map(|claim| (parse_address(&claim.token).unwrap(), claim.block_number))
+            .collect::<Vec<_>>();
+        let mut expected = keys.clone();
+        expected.sort();
+        assert_eq!(keys, expected);
+        assert_eq!(sorted[0].block_number, 0);
+        assert_eq!(sorted[1].block_number, 8);
+    }
+
+    #[test]
+    fn test_arrival_order_kept_by_default() {
+        assert_eq!(cluster(claims(32), &Config::default()).unwrap(), claims(32));
+    }
+
+    #[test]
+    fn test_clustered_file_prunes_more_pages() {
+        let write = |name: &str, config: &Config| {
+            let path = std::env::temp_dir().join(name);
+            let path = path.to_str().unwrap().to_string();
+            let mut writer = ClaimsWriter::create(&path, config).unwrap();
+            for chunk in claims(4096).chunks(1000) {
+                writer.write(chunk).unwrap();
+            }
+            writer.close().unwrap();
+            path
+        };
+        let arrival = Config {
+            data_page_row_limit: 64,
+            bloom_filter_columns: Vec::new(),
+            ..Default::default()
+        };
+        let clustered = Config {
+            z_order: true,
+            sort_keys: vec!["blockNumber".to_string()],
+            ..arrival.clone()
+        };
+        let arrival_path = write("pq_test_cluster_arrival.parquet", &arrival);
+        let clustered_path = write("pq_test_cluster_z_order.parquet", &clustered);
+
+        let mut read = read_reward_file(&clustered_path).unwrap();
+        read.sort_by_key(|claim| claim.block_number);
+        assert_eq!(read, claims(4096));
+
+        let earner = address(1_010);
+        let before = query::claims_for_earner(&arrival_path, &earner).unwrap();
+        let after = query::claims_for_earner(&clustered_path, &earner).unwrap();
+        assert_eq!(before.claims.len(), 64);
+        assert_eq!(after.claims.len(), 64);
+        // arrival order spreads every earner over almost every page
+        assert!(before.stat.pages_skipped < before.stat.pages / 8);
+        assert!(after.stat.pages_skipped >= after.stat.pages - 3);
+
+        let token = address(103);
+        let after = query::claims_for_token(&clustered_path, &token).unwrap();
+        assert_eq!(after.claims.len(), 512);
+        assert!(after.stat.pages_skipped > after.stat.pages / 2);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use crate::config::Config;
+use crate::metadata::{self, Metadata};
+use crate::parquet::{self, ClaimsWriter, READ_BATCH_SIZE};
+use crate::record_batch;
+
+/// What a compaction read and wrote.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompactStat {
+    pub files: usize,
+    pub rows_read: u64,
+    pub rows_written: u64,
+    /// Rows dropped because an earlier file already held the same event.
+    pub duplicates: u64,
+}
+
+/// Merge the claims files `inputs` into `output`.
+///
+/// Claims are deduplicated by event identity, `(transactionHash, logIndex)`, keeping the first
+/// seen.  Claims without an identity, read from JSON written before the log was recorded, are
+/// all kept.  The claims are written in `(blockNumber, logIndex)` order, or the order set by `sortKeys` and
+/// `zOrder`, with the row group sizes in `config`.
+/// The inputs are read in full before [`ClaimsWriter::create`] replaces `output`, so `output`
+/// may be one of the inputs and readers see either the old or the new file.  The inputs are
+/// left in place.
+///
+/// The descriptive footer metadata of the inputs is carried over.  Inputs recorded from
+/// different chains or contracts are refused, and a source hash is only kept when every input
+/// shares it.
+pub fn compact(inputs: &[&str], output: &str, config: &Config) -> eyre::Result<CompactStat> {
+    let mut stat = CompactStat {
+        files: inputs.len(),
+        ..Default::default()
+    };
+    let metadata = merge_metadata(inputs)?;
+    // an event's transaction determines its block, so this key orders and deduplicates at once;
+    // claims without an identity get a key of their own, in the order read
+    let mut claims = BTreeMap::new();
+    let mut unidentified = 0u64;
+    for input in inputs {
+        for batch in parquet::read_record_batches(input, READ_BATCH_SIZE)? {
+            for claim in record_batch::from_record_batch(&batch?)? {
+                stat.rows_read += 1;
+                let sequence = if claim.has_event_id() {
+                    0
+                } else {
+                    unidentified += 1;
+                    unidentified
+                };
+                let key = (
+                    claim.block_number,
+                    claim.log_index,
+                    claim.transaction_hash,
+                    sequence,
+                );
+                match claims.entry(key) {
+                    Entry::Occupied(_) => stat.duplicates += 1,
+                    Entry::Vacant(entry) => {
+                        entry.insert(claim);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut writer = ClaimsWriter::create(output, config)?.with_metadata(metadata);
+    let claims = claims.into_values().collect::<Vec<_>>();
+    for chunk in claims.chunks(READ_BATCH_SIZE) {
+        writer.write(chunk)?;
+    }
+    stat.rows_written = writer.close()?.row_count;
+    Ok(stat)
+}
+
+/// The footer metadata shared by `inputs`.  Files written before the metadata was embedded
+/// contribute nothing.
+fn merge_metadata(inputs: &[&str]) -> eyre::Result<Metadata> {
+    let mut merged: Option<Metadata> = None;
+    for input in inputs {
+        let Some(metadata) = metadata::read_footer_metadata_if_present(input)? else {
+            continue;
+        };
+        let Some(merged) = merged.as_mut() else {
+            merged = Some(metadata);
+            continue;
+        };
+        if merged.chain_id != metadata.chain_id || merged.contract != metadata.contract {
+            return Err(eyre::eyre!(
+                "{} is from chain {:?} contract {:?}, expected chain {:?} contract {:?}",
+                input,
+                metadata.chain_id,
+                metadata.contract,
+                merged.chain_id,
+                merged.contract
+            ));
+        }
+        if merged.source_sha256 != metadata.source_sha256 {
+            merged.source_sha256 = None;
+        }
+        if merged.description.is_empty() {
+            merged.description = metadata.description;
+        }
+    }
+    Ok(merged.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::{fs, path::Path};
+
+    fn claim(block_number: u64, log_index: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            claimer: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            recipient: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            token: "0x3B78576F7D6837500bA3De27A60c7f594934027E".to_string(),
+            claimed_amount: block_number as u128,
+            block_number,
+            transaction_hash: [block_number as u8; 32],
+            log_index,
+            cost: None,
+        }
+    }
+
+    fn write_claims(
+        name: &str,
+        claims: &[eigen_types::RewardsClaimed],
+        metadata: Metadata,
+    ) -> String {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap().to_string();
+        let mut writer = ClaimsWriter::create(&path, &Config::default())
+            .unwrap()
+            .with_metadata(metadata);
+        writer.write(claims).unwrap();
+        writer.close().unwrap();
+        path
+    }
+
+    fn holesky() -> Metadata {
+        Metadata {
+            description: "claims".to_string(),
+            chain_id: Some(17000),
+            contract: Some("0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string()),
+            source_sha256: Some("00".repeat(32)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compact_dedupes_and_sorts() {
+        let first = write_claims(
+            "pq_test_compact_first.parquet",
+            &[claim(3, 0), claim(1, 1), claim(1, 0)],
+            holesky(),
+        );
+        let second = write_claims(
+            "pq_test_compact_second.parquet",
+            &[claim(2, 0), claim(3, 0), claim(4, 5), claim(1, 1)],
+            Metadata {
+                source_sha256: Some("11".repeat(32)),
+                ..holesky()
+            },
+        );
+        let output = std::env::temp_dir().join("pq_test_compact_output.parquet");
+        let output = output.to_str().unwrap();
+        let config = Config {
+            max_row_group_rows: 2,
+            ..Default::default()
+        };
+
+        let stat = compact(&[&first, &second], output, &config).unwrap();
+        assert_eq!(
+            stat,
+            CompactStat {
+                files: 2,
+                rows_read: 7,
+                rows_written: 5,
+                duplicates: 2,
+            }
+        );
+        assert_eq!(
+            parquet::read_reward_file(output).unwrap(),
+            vec![
+                claim(1, 0),
+                claim(1, 1),
+                claim(2, 0),
+                claim(3, 0),
+                claim(4, 5)
+            ]
+        );
+        let reader = SerializedFileReader::new(fs::File::open(output).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+
+        let metadata = metadata::verify(output, None, None).unwrap();
+        assert_eq!(metadata.chain_id, Some(17000));
+        assert_eq!(metadata.contract, holesky().contract);
+        assert_eq!(metadata.description, "claims");
+        assert_eq!(metadata.source_sha256, None);
+        assert_eq!(metadata.row_count, 5);
+        assert_eq!(metadata.first_block, Some(1));
+        assert_eq!(metadata.last_block, Some(4));
+        assert!(!Path::new(&format!("{}.tmp", output)).exists());
+    }
+
+    #[test]
+    fn test_compact_in_place() {
+        let path = write_claims(
+            "pq_test_compact_in_place.parquet",
+            &[claim(2, 0), claim(1, 0), claim(2, 0)],
+            holesky(),
+        );
+        let stat = compact(&[&path], &path, &Config::default()).unwrap();
+        assert_eq!(stat.duplicates, 1);
+        assert_eq!(
+            parquet::read_reward_file(&path).unwrap(),
+            vec![claim(1, 0), claim(2, 0)]
+        );
+        assert_eq!(
+            metadata::verify(&path, None, None).unwrap().source_sha256,
+            holesky().source_sha256
+        );
+    }
+
+    #[test]
+    fn test_compact_refuses_other_chain() {
+        let first = write_claims("pq_test_compact_chain_a.parquet", &[claim(1, 0)], holesky());
+        let second = write_claims(
+            "pq_test_compact_chain_b.parquet",
+            &[claim(2, 0)],
+            Metadata {
+                chain_id: Some(1),
+                ..holesky()
+            },
+        );
+        let output = std::env::temp_dir().join("pq_test_compact_chain_output.parquet");
+        let output = output.to_str().unwrap();
+        let _ = fs::remove_file(output);
+        assert!(compact(&[&first, &second], output, &Config::default()).is_err());
+        assert!(!Path::new(output).exists());
+    }
+
+    /// A claims file whose footer holds only `key_value_metadata`.
+    fn write_claims_with_footer(
+        name: &str,
+        claims: &[eigen_types::RewardsClaimed],
+        key_value_metadata: Vec<::parquet::file::metadata::KeyValue>,
+    ) -> String {
+        let path = std::env::temp_dir().join(name);
+        let properties = ::parquet::file::properties::WriterProperties::builder()
+            .set_key_value_metadata(Some(key_value_metadata))
+            .build();
+        let mut writer = ::parquet::arrow::ArrowWriter::try_new(
+            fs::File::create(&path).unwrap(),
+            record_batch::claims_schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer
+            .write(&record_batch::to_record_batch(claims).unwrap())
+            .unwrap();
+        writer.close().unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_compact_metadata_of_older_files() {
+        let first = write_claims(
+            "pq_test_compact_described.parquet",
+            &[claim(1, 0)],
+            holesky(),
+        );
+        // written before the metadata was embedded
+        let second = write_claims_with_footer(
+            "pq_test_compact_undescribed.parquet",
+            &[claim(2, 0)],
+            vec![::parquet::file::metadata::KeyValue::new(
+                "other".to_string(),
+                "value".to_string(),
+            )],
+        );
+        let output = std::env::temp_dir().join("pq_test_compact_older_output.parquet");
+        let output = output.to_str().unwrap();
+        compact(&[&second, &first], output, &Config::default()).unwrap();
+        assert_eq!(
+            metadata::verify(output, None, None).unwrap().chain_id,
+            Some(17000)
+        );
+
+        // incomplete metadata is not mistaken for none
+        let broken = write_claims_with_footer(
+            "pq_test_compact_broken.parquet",
+            &[claim(3, 0)],
+            vec![::parquet::file::metadata::KeyValue::new(
+                "pq.description".to_string(),
+                "claims".to_string(),
+            )],
+        );
+        let output = std::env::temp_dir().join("pq_test_compact_broken_output.parquet");
+        let output = output.to_str().unwrap();
+        let _ = fs::remove_file(output);
+        assert!(compact(&[&first, &broken], output, &Config::default()).is_err());
+        assert!(!Path::new(output).exists());
+    }
+
+    #[test]
+    fn test_compact_keeps_claims_without_identity() {
+        let unidentified = |claimed_amount| eigen_types::RewardsClaimed {
+            block_number: 0,
+            transaction_hash: [0; 32],
+            claimed_amount,
+            ..claim(0, 0)
+        };
+        let first = write_claims(
+            "pq_test_compact_unidentified_first.parquet",
+            &[unidentified(1), claim(1, 0), unidentified(2)],
+            holesky(),
+        );
+        let second = write_claims(
+            "pq_test_compact_unidentified_second.parquet",
+            &[unidentified(1), claim(1, 0), unidentified(3)],
+            holesky(),
+        );
+        let output = std::env::temp_dir().join("pq_test_compact_unidentified_output.parquet");
+        let output = output.to_str().unwrap();
+
+        let stat = compact(&[&first, &second], output, &Config::default()).unwrap();
+        assert_eq!(stat.rows_read, 6);
+        assert_eq!(stat.duplicates, 1);
+        assert_eq!(
+            parquet::read_reward_file(output).unwrap(),
+            vec![
+                unidentified(1),
+                unidentified(2),
+                unidentified(1),
+                unidentified(3),
+                claim(1, 0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::{collections::BTreeMap, io::ErrorKind, str::FromStr};
+
+use parquet::{
+    basic::Compression,
+    file::properties::{
+        EnabledStatistics, WriterProperties, WriterVersion, DEFAULT_WRITE_BATCH_SIZE,
+    },
+    schema::types::ColumnPath,
+};
+use serde::Deserialize;
+
+use crate::parquet::{DEFAULT_MAX_ROW_GROUP_BYTES, DEFAULT_MAX_ROW_GROUP_ROWS};
+use crate::record_batch;
+
+/// The Parquet writer options.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// The compression codec: `uncompressed`, `snappy`, `lz4`, `gzip(level)` or `zstd(level)`.
+    #[serde(default = "default_compression")]
+    pub compression: String,
+    /// Dictionary encode every column unless overridden in `dictionaryColumns`.
+    #[serde(default = "default_dictionary")]
+    pub dictionary: bool,
+    /// Per column dictionary encoding overrides, e.g. `{ "token": true, "root": false }`.
+    #[serde(rename = "dictionaryColumns", default)]
+    pub dictionary_columns: BTreeMap<String, bool>,
+    /// The target size of a data page in bytes.
+    #[serde(rename = "dataPageSize", default = "default_data_page_size")]
+    pub data_page_size: usize,
+    /// The maximum number of rows in a data page, which bounds how finely the page index can
+    /// prune.
+    #[serde(rename = "dataPageRowLimit", default = "default_data_page_row_limit")]
+    pub data_page_row_limit: usize,
+    /// Start a new row group after this many rows.
+    #[serde(rename = "maxRowGroupRows", default = "default_max_row_group_rows")]
+    pub max_row_group_rows: usize,
+    /// Start a new row group once this many bytes are buffered.
+    #[serde(rename = "maxRowGroupBytes", default = "default_max_row_group_bytes")]
+    pub max_row_group_bytes: usize,
+    /// The columns to write bloom filters for, checked by the equality queries.
+    #[serde(
+        rename = "bloomFilterColumns",
+        default = "default_bloom_filter_columns"
+    )]
+    pub bloom_filter_columns: Vec<String>,
+    /// The expected number of distinct values per column chunk, which sizes the bloom filters.
+    #[serde(rename = "bloomFilterNdv", default = "default_bloom_filter_ndv")]
+    pub bloom_filter_ndv: u64,
+    /// Write the claims as a `token=<addr>/block_bucket=<n>` partitioned directory instead of
+    /// one file.
+    #[serde(default = "default_partitioned")]
+    pub partitioned: bool,
+    /// The number of blocks in each `block_bucket` partition.
+    #[serde(rename = "blockBucketSize", default = "default_block_bucket_size")]
+    pub block_bucket_size: u64,
+    /// Write the rows ordered by these columns, e.g. `["token", "earner", "blockNumber"]`,
+    /// instead of in arrival order.
+    #[serde(rename = "sortKeys", default)]
+    pub sort_keys: Vec<String>,
+    /// Cluster the rows on the Z-order curve over `(token, earner)`, so both columns have
+    /// narrow page and row group ranges.  `sortKeys` then break ties.
+    #[serde(rename = "zOrder", default = "default_z_order")]
+    pub z_order: bool,
+    /// The chain the claims were indexed from, recorded in the file metadata.
+    #[serde(rename = "chainId", default)]
+    pub chain_id: Option<u64>,
+    /// The rewards coordinator address, recorded in the file metadata.
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Mirror the footer metadata to a JSON sidecar next to the file.
+    #[serde(default = "default_sidecar")]
+    pub sidecar: bool,
+    /// Write the claims with every codec and dictionary combination and report the results.
+    #[serde(default = "default_benchmark")]
+    pub benchmark: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            compression: default_compression(),
+            dictionary: default_dictionary(),
+            dictionary_columns: BTreeMap::new(),
+            data_page_size: default_data_page_size(),
+            data_page_row_limit: default_data_page_row_limit(),
+            max_row_group_rows: default_max_row_group_rows(),
+            max_row_group_bytes: default_max_row_group_bytes(),
+            bloom_filter_columns: default_bloom_filter_columns(),
+            bloom_filter_ndv: default_bloom_filter_ndv(),
+            partitioned: default_partitioned(),
+            block_bucket_size: default_block_bucket_size(),
+            sort_keys: Vec::new(),
+            z_order: default_z_order(),
+            chain_id: None,
+            contract: None,
+            sidecar: default_sidecar(),
+            benchmark: default_benchmark(),
+        }
+    }
+}
+
+impl Config {
+    /// The writer properties for the claims table.  Page level statistics are always written
+    /// so readers can use the page index.
+    pub fn writer_properties(&self) -> eyre::Result<WriterProperties> {
+        // Parquet 1.0 pages cannot dictionary encode the fixed length address columns
+        let mut builder = WriterProperties::builder()
+            .set_writer_version(WriterVersion::PARQUET_2_0)
+            .set_compression(Compression::from_str(&self.compression)?)
+            .set_dictionary_enabled(self.dictionary)
+            .set_data_page_size_limit(self.data_page_size)
+            .set_data_page_row_count_limit(self.data_page_row_limit)
+            // page limits are only checked between write batches
+            .set_write_batch_size(self.data_page_row_limit.min(DEFAULT_WRITE_BATCH_SIZE))
+            .set_max_row_group_size(self.max_row_group_rows)
+            .set_statistics_enabled(EnabledStatistics::Page);
+        let schema = record_batch::claims_schema();
+        for (column, enabled) in &self.dictionary_columns {
+            schema.field_with_name(column)?;
+            builder =
+                builder.set_column_dictionary_enabled(ColumnPath::from(column.as_str()), *enabled);
+        }
+        for column in &self.bloom_filter_columns {
+            schema.field_with_name(column)?;
+            builder = builder
+                .set_column_bloom_filter_enabled(ColumnPath::from(column.as_str()), true)
+                .set_column_bloom_filter_ndv(
+                    ColumnPath::from(column.as_str()),
+                    self.bloom_filter_ndv,
+                );
+        }
+        for column in &self.sort_keys {
+            schema.field_with_name(column)?;
+        }
+        Ok(builder.build())
+    }
+
+    /// Whether rows are reordered before they are written.
+    pub fn clustered(&self) -> bool {
+        self.z_order || !self.sort_keys.is_empty()
+    }
+}
+
+fn default_compression() -> String {
+    "uncompressed".to_string()
+}
+
+fn default_dictionary() -> bool {
+    true
+}
+
+fn default_data_page_size() -> usize {
+    1024 * 1024
+}
+
+fn default_data_page_row_limit() -> usize {
+    20_000
+}
+
+fn default_max_row_group_rows() -> usize {
+    DEFAULT_MAX_ROW_GROUP_ROWS
+}
+
+fn default_max_row_group_bytes() -> usize {
+    DEFAULT_MAX_ROW_GROUP_BYTES
+}
+
+fn default_bloom_filter_columns() -> Vec<String> {
+    vec![
+        "root".to_string(),
+        "earner".to_string(),
+        "token".to_string(),
+    ]
+}
+
+fn default_bloom_filter_ndv() -> u64 {
+    10_000
+}
+
+fn default_partitioned() -> bool {
+    false
+}
+
+fn default_block_bucket_size() -> u64 {
+    100_000
+}
+
+fn default_z_order() -> bool {
+    false
+}
+
+fn default_sidecar() -> bool {
+    true
+}
+
+fn default_benchmark() -> bool {
+    false
+}
+
+/// Load the writer options from `PQ_CONFIG_PATH`, or `pq_config.json`.  A missing file gives
+/// the defaults.
+pub fn load_config() -> eyre::Result<Config> {
+    let config_path =
+        std::env::var("PQ_CONFIG_PATH").unwrap_or_else(|_| "pq_config.json".to_string());
+    let config = match std::fs::read_to_string(config_path) {
+        Ok(config) => serde_json::from_str(&config)?,
+        Err(e) if e.kind() == ErrorKind::NotFound => Config::default(),
+        Err(e) => return Err(e.into()),
+    };
+    tracing::info!("Loaded config: {:?}", config);
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::{
+        basic::Encoding,
+        file::reader::{FileReader, SerializedFileReader},
+    };
+
+    #[test]
+    fn test_config_deserialization() {
+        let config = r#"{
+            "compression": "zstd(3)",
+            "dictionary": false,
+            "dictionaryColumns": { "token": true, "earner": true },
+            "dataPageSize": 65536,
+            "dataPageRowLimit": 100,
+            "maxRowGroupRows": 1000,
+            "maxRowGroupBytes": 4096,
+            "bloomFilterColumns": ["earner"],
+            "bloomFilterNdv": 500,
+            "partitioned": true,
+            "blockBucketSize": 1000,
+            "sortKeys": ["token", "earner", "blockNumber"],
+            "zOrder": true,
+            "chainId": 17000,
+            "contract": "0xAcc1fb458a1317E886dB376Fc8141540537E68fE",
+            "sidecar": false,
+            "benchmark": true
+        }"#;
+        let config: Config = serde_json::from_str(config).unwrap();
+        assert_eq!(config.compression, "zstd(3)");
+        assert!(!config.dictionary);
+        assert_eq!(config.dictionary_columns.get("token"), Some(&true));
+        assert_eq!(config.dictionary_columns.get("earner"), Some(&true));
+        assert_eq!(config.data_page_size, 65536);
+        assert_eq!(config.data_page_row_limit, 100);
+        assert_eq!(config.max_row_group_rows, 1000);
+        assert_eq!(config.max_row_group_bytes, 4096);
+        assert_eq!(config.bloom_filter_columns, vec!["earner"]);
+        assert_eq!(config.bloom_filter_ndv, 500);
+        assert!(config.partitioned);
+        assert_eq!(config.block_bucket_size, 1000);
+        assert_eq!(config.sort_keys, vec!["token", "earner", "blockNumber"]);
+        assert!(config.z_order);
+        assert_eq!(config.chain_id, Some(17000));
+        assert_eq!(
+            config.contract.as_deref(),
+            Some("0xAcc1fb458a1317E886dB376Fc8141540537E68fE")
+        );
+        assert!(!config.sidecar);
+        assert!(config.benchmark);
+    }
+
+    #[test]
+    fn test_default_compression() {
+        assert_eq!(default_compression(), "uncompressed");
+    }
+
+    #[test]
+    fn test_default_dictionary() {
+        assert!(default_dictionary());
+    }
+
+    #[test]
+    fn test_default_data_page_size() {
+        assert_eq!(default_data_page_size(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_default_data_page_row_limit() {
+        assert_eq!(default_data_page_row_limit(), 20_000);
+    }
+
+    #[test]
+    fn test_default_max_row_group_rows() {
+        assert_eq!(default_max_row_group_rows(), DEFAULT_MAX_ROW_GROUP_ROWS);
+    }
+
+    #[test]
+    fn test_default_max_row_group_bytes() {
+        assert_eq!(default_max_row_group_bytes(), DEFAULT_MAX_ROW_GROUP_BYTES);
+    }
+
+    #[test]
+    fn test_default_bloom_filter_columns() {
+        assert_eq!(
+            default_bloom_filter_columns(),
+            vec!["root", "earner", "token"]
+        );
+    }
+
+    #[test]
+    fn test_default_bloom_filter_ndv() {
+        assert_eq!(default_bloom_filter_ndv(), 10_000);
+    }
+
+    #[test]
+    fn test_default_partitioned() {
+        assert!(!default_partitioned());
+    }
+
+    #[test]
+    fn test_default_block_bucket_size() {
+        assert_eq!(default_block_bucket_size(), 100_000);
+    }
+
+    #[test]
+    fn test_default_z_order() {
+        assert!(!default_z_order());
+    }
+
+    #[test]
+    fn test_default_sidecar() {
+        assert!(default_sidecar());
+    }
+
+    #[test]
+    fn test_default_benchmark() {
+        assert!(!default_benchmark());
+    }
+
+    #[test]
+    fn test_invalid_options_rejected() {
+        let config = Config {
+            compression: "zstd(99)".to_string(),
+            ..Default::default()
+        };
+        assert!(config.writer_properties().is_err());
+        let config = Config {
+            dictionary_columns: BTreeMap::from([("amount".to_string(), true)]),
+            ..Default::default()
+        };
+        assert!(config.writer_properties().is_err());
+        let config = Config {
+            sort_keys: vec!["amount".to_string()],
+            ..Default::default()
+        };
+        assert!(config.writer_properties().is_err());
+    }
+
+    #[test]
+    fn test_writer_properties_applied() {
+        let path = std::env::temp_dir().join("pq_test_writer_properties.parquet");
+        let path = path.to_str().unwrap();
+        let config = Config {
+            compression: "zstd(3)".to_string(),
+            dictionary: false,
+            dictionary_columns: BTreeMap::from([("token".to_string(), true)]),
+            ..Default::default()
+        };
+        let claim = eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            claimer: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            recipient: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            token: "0x3B78576F7D6837500bA3De27A60c7f594934027E".to_string(),
+            claimed_amount: 1,
+            block_number: 0,
+            transaction_hash: [0; 32],
+            log_index: 0,
+            cost: None,
+        };
+        let mut writer = crate::parquet::ClaimsWriter::create(path, &config).unwrap();
+        writer.write(&vec![claim; 100]).unwrap();
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+        let row_group = reader.metadata().row_group(0);
+        for column in row_group.columns() {
+            // the level is not recorded in the footer
+            assert!(matches!(column.compression(), Compression::ZSTD(_)));
+            let dictionary = column.encodings().contains(&Encoding::RLE_DICTIONARY);
+            assert_eq!(dictionary, column.column_path().string() == "token");
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::Address;
+
+use crate::config::Config;
+use crate::parquet::{self, parse_address, ClaimsWriter};
+
+const TOKEN_KEY: &str = "token";
+const BLOCK_BUCKET_KEY: &str = "block_bucket";
+
+/// Writes claims into a Hive partitioned directory,
+/// `token=<addr>/block_bucket=<n>/part-<i>.parquet`, where `n` is the block number divided by
+/// the bucket size.
+///
+/// Every write adds new parts, so existing partitions are never rewritten.  A part is written
+/// under a `.tmp` name and renamed into place, so readers only see complete files.
+pub struct DatasetWriter {
+    root: PathBuf,
+    config: Config,
+}
+
+impl DatasetWriter {
+    pub fn open(root: &str, config: &Config) -> eyre::Result<Self> {
+        if config.block_bucket_size == 0 {
+            return Err(eyre::eyre!("blockBucketSize must be positive"));
+        }
+        fs::create_dir_all(root)?;
+        Ok(DatasetWriter {
+            root: PathBuf::from(root),
+            config: config.clone(),
+        })
+    }
+
+    /// Append claims as one new part per partition, returning the parts written.
+    pub fn append(
+        &self,
+        rewards_claimed: &[eigen_types::RewardsClaimed],
+    ) -> eyre::Result<Vec<PathBuf>> {
+        let mut partitions: BTreeMap<(Address, u64), Vec<eigen_types::RewardsClaimed>> =
+            BTreeMap::new();
+        for claim in rewards_claimed {
+            let token = parse_address(&claim.token)?;
+            let block_bucket = claim.block_number / self.config.block_bucket_size;
+            partitions
+                .entry((token, block_bucket))
+                .or_default()
+                .push(claim.clone());
+        }
+        let mut parts = Vec::new();
+        for ((token, block_bucket), claims) in partitions {
+            let dir = self
+                .root
+                .join(format!("{}={}", TOKEN_KEY, partition_token(&token)))
+                .join(format!("{}={}", BLOCK_BUCKET_KEY, block_bucket));
+            fs::create_dir_all(&dir)?;
+            let part = dir.join(format!("part-{:05}.parquet", next_part(&dir)?));
+            let mut writer = ClaimsWriter::create(&part.to_string_lossy(), &self.config)?;
+            writer.write(&claims)?;
+            writer.close()?;
+            parts.push(part);
+        }
+        Ok(parts)
+    }
+}
+
+/// Tokens are written in lower case so partition names do not depend on the case sensitivity
+/// of the file system.
+fn partition_token(token: &Address) -> String {
+    format!("{:#x}", token)
+}
+
+fn part_number(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("part-")?
+        .strip_suffix(".parquet")?
+        .parse()
+        .ok()
+}
+
+fn next_part(dir: &Path) -> eyre::Result<u64> {
+    let mut next = 0;
+    for entry in fs::read_dir(dir)? {
+        if let Some(part) = part_number(&entry?.path()) {
+            next = next.max(part + 1);
+        }
+    }
+    Ok(next)
+}
+
+/// One `token=<addr>/block_bucket=<n>` directory and its parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    pub token: Address,
+    pub block_bucket: u64,
+    pub files: Vec<PathBuf>,
+}
+
+/// Restricts a dataset read by partition values.  `None` matches every partition.
+#[derive(Debug, Default, Clone)]
+pub struct PartitionFilter {
+    pub token: Option<Address>,
+    pub block_range: Option<RangeInclusive<u64>>,
+}
+
+/// A partitioned claims directory written by [`DatasetWriter`].
+#[derive(Debug)]
+pub struct Dataset {
+    block_bucket_size: u64,
+    partitions: Vec<Partition>,
+}
+
+impl Dataset {
+    /// Discover the partitions under `root`.  Directories and files that do not follow the
+    /// layout, including parts still being written, are ignored.
+    pub fn open(root: &str, block_bucket_size: u64) -> eyre::Result<Self> {
+        if block_bucket_size == 0 {
+            return Err(eyre::eyre!("blockBucketSize must be positive"));
+        }
+        let mut partitions = Vec::new();
+        for (token, token_dir) in partition_dirs(Path::new(root), TOKEN_KEY)? {
+            let Ok(token) = parse_address(&token) else {
+                continue;
+            };
+            for (block_bucket, bucket_dir) in partition_dirs(&token_dir, BLOCK_BUCKET_KEY)? {
+                let Ok(block_bucket) = block_bucket.parse() else {
+                    continue;
+                };
+                let mut files = fs::read_dir(&bucket_dir)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                files.retain(|path| part_number(path).is_some());
+                files.sort();
+                partitions.push(Partition {
+                    token,
+                    block_bucket,
+                    files,
+                });
+            }
+        }
+        partitions.sort_by_key(|p| (p.token, p.block_bucket));
+        Ok(Dataset {
+            block_bucket_size,
+            partitions,
+        })
+    }
+
+    pub fn partitions(&self) -> &[Partition] {
+        &self.partitions
+    }
+
+    /// The partitions whose values may match `filter`.
+    pub fn select(&self, filter: &PartitionFilter) -> Vec<&Partition> {
+        self.partitions
+            .iter()
+            .filter(|p| filter.token.iter().all(|token| p.token == *token))
+            .filter(|p| {
+                filter.block_range.iter().all(|range| {
+                    let first = p.block_bucket.saturating_mul(self.block_bucket_size);
+                    let last = first.saturating_add(self.block_bucket_size - 1);
+                    first <= *range.end() && *range.start() <= last
+                })
+            })
+            .collect()
+    }
+
+    /// Read the claims matching `filter`, opening only the selected partitions.
+    pub fn read(&self, filter: &PartitionFilter) -> eyre::Result<Vec<eigen_types::RewardsClaimed>> {
+        let mut claims = Vec::new();
+        for partition in self.select(filter) {
+            for file in &partition.files {
+                claims.extend(
+                    parquet::read_reward_file(&file.to_string_lossy())?
+                        .into_iter()
+                        .filter(|claim| {
+                            filter
+                                .block_range
+                                .iter()
+                                .all(|range| range.contains(&claim.block_number))
+                        }),
+                );
+            }
+        }
+        Ok(claims)
+    }
+}
+
+/// The `key=<value>` subdirectories of `dir`.
+fn partition_dirs(dir: &Path, key: &str) -> eyre::Result<Vec<(String, PathBuf)>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let value = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(key)?.strip_prefix('='));
+        if let Some(value) = value {
+            dirs.push((value.to_string(), path.clone()));
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(i: u64) -> Address {
+        Address::left_padding_from(&i.to_be_bytes())
+    }
+
+    /// Two tokens over blocks `[from, to)`, one claim per block.
+    fn claims(from: u64, to: u64) -> Vec<eigen_types::RewardsClaimed> {
+        (from..to)
+            .map(|i| eigen_types::RewardsClaimed {
+                root: [1; 32],
+                earner: address(1_000 + i).to_checksum(None),
+                claimer: address(1).to_checksum(None),
+                recipient: address(1).to_checksum(None),
+                token: address(100 + i % 2).to_checksum(None),
+                claimed_amount: i as u128,
+                block_number: i,
+                transaction_hash: [0; 32],
+                log_index: 0,
+                cost: None,
+            })
+            .collect()
+    }
+
+    fn open_dataset(name: &str) -> (String, Config) {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        let config = Config {
+            block_bucket_size: 100,
+            ..Default::default()
+        };
+        (root.to_string_lossy().into_owned(), config)
+    }
+
+    #[test]
+    fn test_partitions_discovered() {
+        let (root, config) = open_dataset("pq_test_dataset_partitions");
+        let writer = DatasetWriter::open(&root, &config).unwrap();
+        let parts = writer.append(&claims(0, 300)).unwrap();
+        assert_eq!(parts.len(), 6);
+        let token = partition_token(&address(100));
+        assert!(Path::new(&root)
+            .join(format!("token={}/block_bucket=2/part-00000.parquet", token))
+            .exists());
+
+        // not part of the layout
+        fs::create_dir_all(Path::new(&root).join("_tmp")).unwrap();
+        fs::write(Path::new(&root).join("_SUCCESS"), "").unwrap();
+        fs::create_dir_all(Path::new(&root).join("token=junk/block_bucket=0")).unwrap();
+        fs::create_dir_all(Path::new(&root).join(format!("token={}/block_bucket=junk", token)))
+            .unwrap();
+
+        let dataset = Dataset::open(&root, config.block_bucket_size).unwrap();
+        assert_eq!(dataset.partitions().len(), 6);
+        let mut read = dataset.read(&PartitionFilter::default()).unwrap();
+        read.sort_by_key(|claim| claim.block_number);
+        assert_eq!(read, claims(0, 300));
+    }
+
+    #[test]
+    fn test_partition_pruning() {
+        let (root, config) = open_dataset("pq_test_dataset_pruning");
+        let writer = DatasetWriter::open(&root, &config).unwrap();
+        writer.append(&claims(0, 300)).unwrap();
+        let dataset = Dataset::open(&root, config.block_bucket_size).unwrap();
+
+        let filter = PartitionFilter {
+            token: Some(address(101)),
+            ..Default::default()
+        };
+        assert_eq!(dataset.select(&filter).len(), 3);
+        let read = dataset.read(&filter).unwrap();
+        assert_eq!(read.len(), 150);
+        assert!(read
+            .iter()
+            .all(|claim| claim.token == address(101).to_checksum(None)));
+
+        let filter = PartitionFilter {
+            token: Some(address(100)),
+            block_range: Some(150..=199),
+        };
+        let selected = dataset.select(&filter);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].block_bucket, 1);
+        let read = dataset.read(&filter).unwrap();
+        assert_eq!(read.len(), 25);
+        assert!(read
+            .iter()
+            .all(|claim| (150..=199).contains(&claim.block_number)));
+
+        let filter = PartitionFilter {
+            token: Some(address(102)),
+            ..Default::default()
+        };
+        assert!(dataset.read(&filter).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_keeps_existing_parts() {
+        let (root, config) = open_dataset("pq_test_dataset_append");
+        let writer = DatasetWriter::open(&root, &config).unwrap();
+        let first = writer.append(&claims(0, 150)).unwrap();
+        let before = first
+            .iter()
+            .map(|part| fs::read(part).unwrap())
+            .collect::<Vec<_>>();
+
+        let second = writer.append(&claims(150, 250)).unwrap();
+        // the second bucket gains a part, the third is new
+        assert!(second
+            .iter()
+            .any(|part| part.ends_with("block_bucket=1/part-00001.parquet")));
+        assert!(second
+            .iter()
+            .any(|part| part.ends_with("block_bucket=2/part-00000.parquet")));
+        for (part, bytes) in first.iter().zip(before) {
+            assert_eq!(fs::read(part).unwrap(), bytes);
+        }
+
+        let dataset = Dataset::open(&root, config.block_bucket_size).unwrap();
+        assert_eq!(dataset.partitions().len(), 6);
+        let mut read = dataset.read(&PartitionFilter::default()).unwrap();
+        read.sort_by_key(|claim| claim.block_number);
+        assert_eq!(read, claims(0, 250));
+    }
+
+    #[test]
+    fn test_zero_block_bucket_size_rejected() {
+        let (root, config) = open_dataset("pq_test_dataset_zero_bucket");
+        let config = Config {
+            block_bucket_size: 0,
+            ..config
+        };
+        assert!(DatasetWriter::open(&root, &config).is_err());
+        assert!(Dataset::open(&root, 0).is_err());
+    }
+}
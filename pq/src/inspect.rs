@@ -0,0 +1,366 @@
+use std::{fmt, fs, path::Path};
+
+use alloy::primitives::{hex, I256};
+use parquet::{
+    basic::{Compression, LogicalType, Type as PhysicalType},
+    file::{
+        metadata::ColumnChunkMetaData,
+        reader::{FileReader, SerializedFileReader},
+    },
+    schema::types::ColumnDescriptor,
+};
+
+use crate::parquet::{address_from_bytes, amount_from_bytes};
+
+/// Footer values longer than this are elided, chiefly the base64 `ARROW:schema`.
+const MAX_METADATA_VALUE_LEN: usize = 120;
+
+/// A leaf column of the file schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub physical_type: PhysicalType,
+    /// Width of a fixed length column, otherwise `-1`.
+    pub type_length: i32,
+    pub logical_type: Option<LogicalType>,
+}
+
+/// One column chunk of a row group.  Statistics are decoded by the column's type: 20 byte
+/// columns as checksummed addresses, decimals by their width and scale, unsigned integers as
+/// unsigned and other binary as `0x` hex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChunkSummary {
+    pub name: String,
+    pub compression: Compression,
+    pub compressed_bytes: i64,
+    pub uncompressed_bytes: i64,
+    pub null_count: Option<u64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+impl ColumnChunkSummary {
+    /// Uncompressed over compressed size.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_bytes == 0 {
+            return 0.0;
+        }
+        self.uncompressed_bytes as f64 / self.compressed_bytes as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowGroupSummary {
+    pub rows: i64,
+    pub compressed_bytes: i64,
+    pub uncompressed_bytes: i64,
+    pub columns: Vec<ColumnChunkSummary>,
+}
+
+/// What `pq inspect` reports about a Parquet file, read from the footer alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSummary {
+    pub created_by: Option<String>,
+    pub rows: i64,
+    pub columns: Vec<ColumnSchema>,
+    pub row_groups: Vec<RowGroupSummary>,
+    pub key_value_metadata: Vec<(String, Option<String>)>,
+}
+
+/// Summarise the schema, row groups, column chunk statistics and key-value metadata of the
+/// Parquet file at `path`.
+pub fn inspect(path: &str) -> eyre::Result<FileSummary> {
+    let reader = SerializedFileReader::new(fs::File::open(Path::new(path))?)?;
+    let metadata = reader.metadata();
+    let file_metadata = metadata.file_metadata();
+    let columns = file_metadata
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| ColumnSchema {
+            name: column.name().to_string(),
+            physical_type: column.physical_type(),
+            type_length: column.type_length(),
+            logical_type: column.logical_type(),
+        })
+        .collect();
+    let row_groups = metadata
+        .row_groups()
+        .iter()
+        .map(|row_group| {
+            let columns = row_group
+                .columns()
+                .iter()
+                .map(summarise_column_chunk)
+                .collect::<eyre::Result<Vec<_>>>()?;
+            Ok(RowGroupSummary {
+                rows: row_group.num_rows(),
+                compressed_bytes: row_group.compressed_size(),
+                uncompressed_bytes: row_group.total_byte_size(),
+                columns,
+            })
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let key_value_metadata = file_metadata
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .map(|kv| (kv.key.clone(), kv.value.clone()))
+        .collect();
+    Ok(FileSummary {
+        created_by: file_metadata.created_by().map(str::to_string),
+        rows: file_metadata.num_rows(),
+        columns,
+        row_groups,
+        key_value_metadata,
+    })
+}
+
+fn summarise_column_chunk(column: &ColumnChunkMetaData) -> eyre::Result<ColumnChunkSummary> {
+    let descr = column.column_descr();
+    let statistics = column.statistics();
+    let decode = |bytes: Option<&[u8]>| bytes.map(|bytes| decode_value(descr, bytes)).transpose();
+    Ok(ColumnChunkSummary {
+        name: descr.name().to_string(),
+        compression: column.compression(),
+        compressed_bytes: column.compressed_size(),
+        uncompressed_bytes: column.uncompressed_size(),
+        null_count: statistics.and_then(|s| s.null_count_opt()),
+        min: decode(statistics.and_then(|s| s.min_bytes_opt()))?,
+        max: decode(statistics.and_then(|s| s.max_bytes_opt()))?,
+    })
+}
+
+/// Decode a plain encoded statistics value of the column `descr`.
+fn decode_value(descr: &ColumnDescriptor, bytes: &[u8]) -> eyre::Result<String> {
+    let unsigned = matches!(
+        descr.logical_type(),
+        Some(LogicalType::Integer {
+            is_signed: false,
+            ..
+        })
+    );
+    Ok(match descr.physical_type() {
+        PhysicalType::INT64 if unsigned => u64::from_le_bytes(bytes.try_into()?).to_string(),
+        PhysicalType::INT64 => i64::from_le_bytes(bytes.try_into()?).to_string(),
+        PhysicalType::INT32 if unsigned => u32::from_le_bytes(bytes.try_into()?).to_string(),
+        PhysicalType::INT32 => i32::from_le_bytes(bytes.try_into()?).to_string(),
+        PhysicalType::FIXED_LEN_BYTE_ARRAY
+            if matches!(descr.logical_type(), Some(LogicalType::Decimal { .. })) =>
+        {
+            decode_decimal(descr, bytes)?
+        }
+        PhysicalType::FIXED_LEN_BYTE_ARRAY if bytes.len() == 20 => address_from_bytes(bytes)?,
+        PhysicalType::BYTE_ARRAY if matches!(descr.logical_type(), Some(LogicalType::String)) => {
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        _ => format!("0x{}", hex::encode(bytes)),
+    })
+}
+
+/// Decode a `FIXED_LEN_BYTE_ARRAY` decimal by the column's width.  The 32 byte amounts of the
+/// claims table are unsigned; other widths up to 32 bytes are read as two's complement, as the
+/// format specifies, and wider values are shown as hex.
+fn decode_decimal(descr: &ColumnDescriptor, bytes: &[u8]) -> eyre::Result<String> {
+    let width = descr.type_length() as usize;
+    if width == 32 {
+        return Ok(amount_from_bytes(bytes)?.to_string());
+    }
+    if bytes.len() != width || width > 32 {
+        return Ok(format!("0x{}", hex::encode(bytes)));
+    }
+    let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+    let mut extended = [if negative { 0xff } else { 0 }; 32];
+    extended[32 - width..].copy_from_slice(bytes);
+    let unscaled = I256::from_be_bytes(extended);
+    let scale = descr.type_scale().max(0) as usize;
+    if scale == 0 {
+        return Ok(unscaled.to_string());
+    }
+    let digits = format!("{:0>width$}", unscaled.unsigned_abs(), width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    let sign = if unscaled.is_negative() { "-" } else { "" };
+    Ok(format!("{}{}.{}", sign, integer, fraction))
+}
+
+impl fmt::Display for ColumnSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.physical_type)?;
+        if self.physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+            write!(f, "({})", self.type_length)?;
+        }
+        if let Some(logical_type) = &self.logical_type {
+            write!(f, " {:?}", logical_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(created_by) = &self.created_by {
+            writeln!(f, "Created by: {}", created_by)?;
+        }
+        writeln!(f, "Rows: {}", self.rows)?;
+        writeln!(f, "Schema:")?;
+        for column in &self.columns {
+            writeln!(f, "  {}", column)?;
+        }
+        writeln!(f, "Row groups: {}", self.row_groups.len())?;
+        for (i, row_group) in self.row_groups.iter().enumerate() {
+            writeln!(
+                f,
+                "Row group {}: {} rows, {} bytes compressed, {} bytes uncompressed",
+                i, row_group.rows, row_group.compressed_bytes, row_group.uncompressed_bytes
+            )?;
+            for column in &row_group.columns {
+                writeln!(
+                    f,
+                    "  {:<16} {:<20} {:>10} {:>10} {:>6.2}x nulls {} min {} max {}",
+                    column.name,
+                    column.compression.to_string(),
+                    column.compressed_bytes,
+                    column.uncompressed_bytes,
+                    column.compression_ratio(),
+                    column
+                        .null_count
+                        .map_or_else(|| "-".to_string(), |n| n.to_string()),
+                    column.min.as_deref().unwrap_or("-"),
+                    column.max.as_deref().unwrap_or("-"),
+                )?;
+            }
+        }
+        writeln!(f, "Key-value metadata:")?;
+        for (key, value) in &self.key_value_metadata {
+            match value {
+                Some(value) if value.len() > MAX_METADATA_VALUE_LEN => {
+                    writeln!(f, "  {} = <{} bytes>", key, value.len())?
+                }
+                Some(value) => writeln!(f, "  {} = {}", key, value)?,
+                None => writeln!(f, "  {}", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::metadata::Metadata;
+    use crate::parquet::ClaimsWriter;
+
+    const EARNER_A: &str = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+    const EARNER_B: &str = "0xAcc1fb458a1317E886dB376Fc8141540537E68fE";
+
+    fn claim(earner: &str, claimed_amount: u128, block_number: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: earner.to_string(),
+            claimer: earner.to_string(),
+            recipient: earner.to_string(),
+            token: earner.to_string(),
+            claimed_amount,
+            block_number,
+            transaction_hash: [2; 32],
+            log_index: 0,
+            cost: None,
+        }
+    }
+
+    fn column<'a>(row_group: &'a RowGroupSummary, name: &str) -> &'a ColumnChunkSummary {
+        row_group.columns.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_inspect_claims_file() {
+        let path = std::env::temp_dir().join("pq_test_inspect.parquet");
+        let path = path.to_str().unwrap();
+        let config = Config {
+            max_row_group_rows: 2,
+            ..Default::default()
+        };
+        let mut writer = ClaimsWriter::create(path, &config)
+            .unwrap()
+            .with_metadata(Metadata {
+                chain_id: Some(17000),
+                ..Default::default()
+            });
+        writer
+            .write(&[
+                claim(EARNER_B, 5, 10),
+                claim(EARNER_A, u128::MAX, 11),
+                claim(EARNER_A, 7, 12),
+            ])
+            .unwrap();
+        writer.close().unwrap();
+
+        let summary = inspect(path).unwrap();
+        assert_eq!(summary.rows, 3);
+        assert_eq!(summary.row_groups.len(), 2);
+        assert_eq!(summary.row_groups[0].rows, 2);
+        let amount = summary
+            .columns
+            .iter()
+            .find(|c| c.name == "claimedAmount")
+            .unwrap();
+        assert_eq!(amount.physical_type, PhysicalType::FIXED_LEN_BYTE_ARRAY);
+        assert_eq!(amount.type_length, 32);
+        assert!(matches!(
+            amount.logical_type,
+            Some(LogicalType::Decimal { precision: 76, .. })
+        ));
+
+        let first = &summary.row_groups[0];
+        let earner = column(first, "earner");
+        assert_eq!(earner.min.as_deref(), Some(EARNER_A));
+        assert_eq!(earner.max.as_deref(), Some(EARNER_B));
+        assert_eq!(earner.null_count, Some(0));
+        let claimed_amount = column(first, "claimedAmount");
+        assert_eq!(claimed_amount.min.as_deref(), Some("5"));
+        assert_eq!(
+            claimed_amount.max.as_deref(),
+            Some(u128::MAX.to_string().as_str())
+        );
+        assert!(claimed_amount.compression_ratio() > 0.0);
+        let block_number = column(&summary.row_groups[1], "blockNumber");
+        assert_eq!(block_number.min.as_deref(), Some("12"));
+        assert_eq!(block_number.max.as_deref(), Some("12"));
+
+        assert!(summary
+            .key_value_metadata
+            .contains(&("pq.chain_id".to_string(), Some("17000".to_string()))));
+        let report = summary.to_string();
+        assert!(report.contains("Row group 1: 1 rows"));
+        assert!(report.contains("pq.chain_id = 17000"));
+        assert!(report.contains("ARROW:schema = <"));
+    }
+
+    #[test]
+    fn test_inspect_other_decimal_widths() {
+        let path = std::env::temp_dir().join("pq_test_inspect_decimal128.parquet");
+        let values = arrow::array::Decimal128Array::from(vec![-12345, 600])
+            .with_precision_and_scale(38, 2)
+            .unwrap();
+        let batch = arrow::array::RecordBatch::try_from_iter([(
+            "price",
+            std::sync::Arc::new(values) as arrow::array::ArrayRef,
+        )])
+        .unwrap();
+        let mut writer = ::parquet::arrow::ArrowWriter::try_new(
+            fs::File::create(&path).unwrap(),
+            batch.schema(),
+            None,
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let summary = inspect(path.to_str().unwrap()).unwrap();
+        assert_eq!(summary.columns[0].type_length, 16);
+        let price = column(&summary.row_groups[0], "price");
+        assert_eq!(price.min.as_deref(), Some("-123.45"));
+        assert_eq!(price.max.as_deref(), Some("6.00"));
+    }
+}
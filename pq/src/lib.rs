@@ -0,0 +1,14 @@
+pub mod cluster;
+pub mod compact;
+pub mod config;
+pub mod dataset;
+pub mod inspect;
+pub mod metadata;
+pub mod parquet;
+pub mod query;
+pub mod record_batch;
+pub mod sink;
+#[cfg(feature = "sql")]
+pub mod sql;
+pub mod storage;
+pub mod submission;
@@ -1,42 +1,492 @@
-mod metadata;
-mod parquet;
+use alloy::primitives::Address;
+use pq::config::{self, Config};
+use pq::dataset::{Dataset, DatasetWriter, PartitionFilter};
+use pq::metadata::{self, Metadata};
+use pq::{inspect, parquet, query, storage, submission};
+use std::path::Path;
 
-use metadata::Metadata;
+// File paths
+const JSON_FILE_PATH: &str = "rewards_claimed.json";
+const PARQUET_FILE_PATH: &str = "rewards_coordinator.parquet";
+const DATASET_PATH: &str = "rewards_coordinator";
+const SUBMISSION_JSON_PATH: &str = "rewards_submissions.json";
+const SUBMISSION_FILE_PATH: &str = "rewards_submission.parquet";
+const STRATEGY_FILE_PATH: &str = "rewards_submission_strategy.parquet";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     init_logging();
 
-    // File paths
-    let json_file_path = "rewards_claimed.json";
-    let parquet_file_path = "rewards_coordinator.parquet";
-    let metadata_file_path = "rewards_coordinator_metadata.json";
+    let config = config::load_config()?;
 
+    match std::env::args().nth(1).as_deref() {
+        None | Some("write") => write(&config)?,
+        Some("verify") => verify()?,
+        Some("compact") => compact(&config)?,
+        Some("upload") => upload(&config)?,
+        Some("stat") => stat()?,
+        Some("inspect") => inspect()?,
+        #[cfg(feature = "sql")]
+        Some("query") => sql_query()?,
+        Some(command) => return Err(format!("Unknown command: {}", command).into()),
+    }
+
+    Ok(())
+}
+
+/// Convert the JSON datasets to Parquet and report the read and query timings.
+fn write(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     // Read the JSON file
-    let rewards_claimed = read_reward_json(json_file_path)?;
+    let rewards_claimed = read_reward_json(JSON_FILE_PATH)?;
 
     let write_instant = std::time::Instant::now();
-    // Write the Parquet file
-    parquet::write_parquet_file(parquet_file_path, &rewards_claimed)?;
-    println!("Write time: {:?} us", write_instant.elapsed().as_micros());
-
-    // Create some metadata
+    // Write the Parquet file, describing it in the footer
     let metadata = Metadata {
-        description: String::from("Parquet file"),
-        row_count: rewards_claimed.len() as u64,
+        description: String::from("Rewards coordinator claims"),
+        chain_id: config.chain_id,
+        contract: config.contract.clone(),
+        source_sha256: Some(metadata::source_sha256(JSON_FILE_PATH)?),
+        ..Default::default()
     };
+    let mut writer =
+        parquet::ClaimsWriter::create(PARQUET_FILE_PATH, config)?.with_metadata(metadata);
+    writer.write(&rewards_claimed)?;
+    let metadata = writer.close()?;
+    println!("Write time: {:?} us", write_instant.elapsed().as_micros());
 
-    // Write the metadata sidecar
-    metadata::write_metadata_sidecar(metadata_file_path, &metadata)?;
-    println!("Parquet file and metadata sidecar written successfully.");
+    // Mirror the footer metadata to the sidecar
+    if config.sidecar {
+        metadata::write_metadata_sidecar(&metadata::sidecar_path(PARQUET_FILE_PATH), &metadata)?;
+        println!("Parquet file and metadata sidecar written successfully.");
+    }
 
     let read_instant = std::time::Instant::now();
-    let stat = parquet::read_reward_file_stat(parquet_file_path)?;
+    let stat = parquet::read_reward_file_stat(PARQUET_FILE_PATH)?;
     println!(
         "Read stat time: {:?} us",
         read_instant.elapsed().as_micros()
     );
     println!("Claimed amount stat: {:?}", stat);
 
+    let read_instant = std::time::Instant::now();
+    let stat = parquet::read_reward_file_stat_arrow(PARQUET_FILE_PATH)?;
+    println!(
+        "Read arrow stat time: {:?} us",
+        read_instant.elapsed().as_micros()
+    );
+    println!("Claimed amount stat: {:?}", stat);
+
+    let read_instant = std::time::Instant::now();
+    let stat = storage::read_reward_file_stat_mmap(PARQUET_FILE_PATH)?;
+    println!(
+        "Read mmap stat time: {:?} us",
+        read_instant.elapsed().as_micros()
+    );
+    println!("Claimed amount stat: {:?}", stat);
+
+    let read_instant = std::time::Instant::now();
+    let cost_stat = parquet::read_claim_cost_stat(PARQUET_FILE_PATH)?;
+    println!(
+        "Read claim cost stat time: {:?} us",
+        read_instant.elapsed().as_micros()
+    );
+    println!("Claim cost stat: {:?}", cost_stat);
+
+    if let Some(claim) = rewards_claimed.first() {
+        let query_instant = std::time::Instant::now();
+        let result = query::claims_for_earner(PARQUET_FILE_PATH, &claim.earner)?;
+        println!(
+            "Query earner time: {:?} us",
+            query_instant.elapsed().as_micros()
+        );
+        println!("Claims for {}: {}", claim.earner, result.claims.len());
+        println!("Query stat: {:?}", result.stat);
+    }
+
+    if config.partitioned {
+        let write_instant = std::time::Instant::now();
+        let writer = DatasetWriter::open(DATASET_PATH, config)?;
+        let parts = writer.append(&rewards_claimed)?;
+        println!(
+            "Dataset write time: {:?} us, {} parts",
+            write_instant.elapsed().as_micros(),
+            parts.len()
+        );
+
+        let dataset = Dataset::open(DATASET_PATH, config.block_bucket_size)?;
+        if let Some(claim) = rewards_claimed.first() {
+            let filter = PartitionFilter {
+                token: Some(claim.token.parse()?),
+                ..Default::default()
+            };
+            let read_instant = std::time::Instant::now();
+            let claims = dataset.read(&filter)?;
+            println!(
+                "Dataset read time: {:?} us, {} of {} partitions, {} claims for {}",
+                read_instant.elapsed().as_micros(),
+                dataset.select(&filter).len(),
+                dataset.partitions().len(),
+                claims.len(),
+                claim.token
+            );
+        }
+    }
+
+    match read_submission_json(SUBMISSION_JSON_PATH)? {
+        Some(rewards_submissions) => {
+            submission::write_submissions_parquet_file(
+                SUBMISSION_FILE_PATH,
+                STRATEGY_FILE_PATH,
+                &rewards_submissions,
+            )?;
+            let offered = submission::read_rewards_offered(SUBMISSION_FILE_PATH, 0, u32::MAX)?;
+            println!("Rewards offered: {:?}", offered);
+        }
+        None => println!(
+            "No {}, skipping the submission tables",
+            SUBMISSION_JSON_PATH
+        ),
+    }
+
+    if config.benchmark {
+        benchmark_writer_options(config, &rewards_claimed)?;
+        benchmark_parallel_stat(config)?;
+        benchmark_clustering(config)?;
+    }
+
+    Ok(())
+}
+
+/// Read the claimed amount statistics of a large synthetic file with 1 to N threads, doubling up
+/// to the available parallelism, and report the speedup over one thread.
+fn benchmark_parallel_stat(config: &Config) -> eyre::Result<()> {
+    let benchmark_file_path = "rewards_coordinator_synthetic.parquet";
+    let rows = 4_000_000u64;
+    let options = Config {
+        max_row_group_rows: 100_000,
+        ..config.clone()
+    };
+    let mut writer = parquet::ClaimsWriter::create(benchmark_file_path, &options)?;
+    for start in (0..rows).step_by(100_000) {
+        let claims = (start..start + 100_000)
+            .map(synthetic_claim)
+            .collect::<Vec<_>>();
+        writer.write(&claims)?;
+    }
+    writer.close()?;
+
+    let max_threads = std::thread::available_parallelism()?.get();
+    let mut threads = std::iter::successors(Some(1), |t| Some(t * 2))
+        .take_while(|t| *t < max_threads)
+        .collect::<Vec<_>>();
+    threads.push(max_threads);
+    println!(
+        "| {:>7} | {:>14} | {:>7} |",
+        "Threads", "Read Time (us)", "Speedup"
+    );
+    let mut single_thread_time = None;
+    for threads in threads {
+        let read_instant = std::time::Instant::now();
+        let stat = parquet::read_reward_file_stat_parallel(benchmark_file_path, threads)?;
+        let read_time = read_instant.elapsed().as_micros();
+        if stat.count != rows {
+            return Err(eyre::eyre!(
+                "Read {} rows on {} threads, expected {}",
+                stat.count,
+                threads,
+                rows
+            ));
+        }
+        let single_thread_time = *single_thread_time.get_or_insert(read_time);
+        println!(
+            "| {:>7} | {:>14} | {:>6.2}x |",
+            threads,
+            read_time,
+            single_thread_time as f64 / read_time as f64
+        );
+    }
+    std::fs::remove_file(benchmark_file_path)?;
+    Ok(())
+}
+
+/// Write a synthetic file in arrival order, sorted by `(token, earner, blockNumber)` and
+/// Z-ordered over `(token, earner)`, and report the file size and how long an earner and a
+/// token query take and how many of their pages the page index skips.
+fn benchmark_clustering(config: &Config) -> eyre::Result<()> {
+    let benchmark_file_path = "rewards_coordinator_clustered.parquet";
+    let rows = 500_000u64;
+    let claims = (0..rows).map(synthetic_claim).collect::<Vec<_>>();
+    let base = Config {
+        compression: "zstd(3)".to_string(),
+        max_row_group_rows: 100_000,
+        sort_keys: Vec::new(),
+        z_order: false,
+        ..config.clone()
+    };
+    let layouts = [
+        ("arrival", base.clone()),
+        (
+            "sorted",
+            Config {
+                sort_keys: vec![
+                    "token".to_string(),
+                    "earner".to_string(),
+                    "blockNumber".to_string(),
+                ],
+                ..base.clone()
+            },
+        ),
+        (
+            "z-order",
+            Config {
+                z_order: true,
+                sort_keys: vec!["blockNumber".to_string()],
+                ..base.clone()
+            },
+        ),
+    ];
+    let earner = synthetic_claim(rows / 2).earner;
+    let token = synthetic_claim(rows / 2).token;
+    println!(
+        "| {:<8} | {:>15} | {:>17} | {:>16} | {:>17} | {:>15} | {:>16} |",
+        "Layout",
+        "Write Time (us)",
+        "Storage Size (kB)",
+        "Earner Time (us)",
+        "Earner Pages Read",
+        "Token Time (us)",
+        "Token Pages Read"
+    );
+    for (layout, options) in layouts {
+        let write_instant = std::time::Instant::now();
+        let mut writer = parquet::ClaimsWriter::create(benchmark_file_path, &options)?;
+        writer.write(&claims)?;
+        writer.close()?;
+        let write_time = write_instant.elapsed().as_micros();
+        let size = std::fs::metadata(benchmark_file_path)?.len() / 1024;
+
+        let query_instant = std::time::Instant::now();
+        let by_earner = query::claims_for_earner(benchmark_file_path, &earner)?;
+        let earner_time = query_instant.elapsed().as_micros();
+        let query_instant = std::time::Instant::now();
+        let by_token = query::claims_for_token(benchmark_file_path, &token)?;
+        let token_time = query_instant.elapsed().as_micros();
+        let pages_read =
+            |stat: &query::QueryStat| format!("{}/{}", stat.pages - stat.pages_skipped, stat.pages);
+        println!(
+            "| {:<8} | {:>15} | {:>17} | {:>16} | {:>17} | {:>15} | {:>16} |",
+            layout,
+            write_time,
+            size,
+            earner_time,
+            pages_read(&by_earner.stat),
+            token_time,
+            pages_read(&by_token.stat)
+        );
+    }
+    std::fs::remove_file(benchmark_file_path)?;
+    Ok(())
+}
+
+/// A claim with distinct amounts, earners spread over 1,000 addresses and tokens over 7.
+fn synthetic_claim(i: u64) -> eigen_types::RewardsClaimed {
+    let address = |n: u64| Address::left_padding_from(&n.to_be_bytes()).to_checksum(None);
+    eigen_types::RewardsClaimed {
+        root: [1; 32],
+        earner: address(i % 1000),
+        claimer: address(i % 1000),
+        recipient: address(i % 1000),
+        token: address(i % 7 + 1),
+        claimed_amount: i as u128 * 1_000_000_000,
+        block_number: i / 10,
+        transaction_hash: [0; 32],
+        log_index: i % 10,
+        cost: None,
+    }
+}
+
+/// `pq verify [parquet] [sidecar]`: check the footer metadata against the rows in the file,
+/// the sidecar and the source JSON.  Without arguments the default files are checked, skipping
+/// a sidecar or source that does not exist.
+fn verify() -> eyre::Result<()> {
+    let existing = |path: &'static str| Some(path).filter(|path| Path::new(path).exists());
+    let parquet_file_path = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| PARQUET_FILE_PATH.to_string());
+    let (sidecar_path, source_path) = match std::env::args().nth(3) {
+        Some(sidecar_path) => (Some(sidecar_path), None),
+        None if std::env::args().nth(2).is_none() => (
+            Some(metadata::sidecar_path(PARQUET_FILE_PATH)).filter(|path| Path::new(path).exists()),
+            existing(JSON_FILE_PATH),
+        ),
+        None => (None, None),
+    };
+    let metadata = metadata::verify(&parquet_file_path, sidecar_path.as_deref(), source_path)?;
+    println!("Verified {}: {:?}", parquet_file_path, metadata);
+    Ok(())
+}
+
+/// Write the claims with each codec, with and without dictionary encoding, and report the file
+/// size and the write and read times.
+fn benchmark_writer_options(
+    config: &Config,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> eyre::Result<()> {
+    let benchmark_file_path = "rewards_coordinator_benchmark.parquet";
+    let codecs = [
+        "uncompressed",
+        "snappy",
+        "lz4",
+        "gzip(6)",
+        "zstd(1)",
+        "zstd(3)",
+        "zstd(9)",
+    ];
+    println!(
+        "| {:<12} | {:<10} | {:>15} | {:>14} | {:>17} |",
+        "Compression", "Dictionary", "Write Time (us)", "Read Time (us)", "Storage Size (kB)"
+    );
+    for compression in codecs {
+        for dictionary in [true, false] {
+            let options = Config {
+                compression: compression.to_string(),
+                dictionary,
+                dictionary_columns: Default::default(),
+                ..config.clone()
+            };
+            let write_instant = std::time::Instant::now();
+            let mut writer = parquet::ClaimsWriter::create(benchmark_file_path, &options)?;
+            writer.write(rewards_claimed)?;
+            writer.close()?;
+            let write_time = write_instant.elapsed().as_micros();
+
+            let read_instant = std::time::Instant::now();
+            parquet::read_reward_file_stat_arrow(benchmark_file_path)?;
+            let read_time = read_instant.elapsed().as_micros();
+
+            let size = std::fs::metadata(benchmark_file_path)?.len() / 1024;
+            println!(
+                "| {:<12} | {:<10} | {:>15} | {:>14} | {:>17} |",
+                compression, dictionary, write_time, read_time, size
+            );
+        }
+    }
+    std::fs::remove_file(benchmark_file_path)?;
+    Ok(())
+}
+
+/// `pq compact <output> <input>...`: merge the inputs into `output`, then remove the inputs
+/// that were merged.  `output` may be one of the inputs.
+fn compact(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    let Some((output, inputs)) = args.split_first() else {
+        return Err("Usage: pq compact <output> <input>...".into());
+    };
+    let inputs = inputs.iter().map(String::as_str).collect::<Vec<_>>();
+    let compact_instant = std::time::Instant::now();
+    let stat = pq::compact::compact(&inputs, output, config)?;
+    for input in inputs.iter().filter(|input| *input != output) {
+        std::fs::remove_file(input)?;
+    }
+    if config.sidecar {
+        let metadata = metadata::read_footer_metadata(output)?;
+        metadata::write_metadata_sidecar(&metadata::sidecar_path(output), &metadata)?;
+    }
+    println!(
+        "Compact time: {:?} us, {:?}",
+        compact_instant.elapsed().as_micros(),
+        stat
+    );
+    Ok(())
+}
+
+/// `pq upload <location>`: write the JSON claims to a local path or `s3://<bucket>/<key>`.
+fn upload(config: &Config) -> eyre::Result<()> {
+    let location = std::env::args()
+        .nth(2)
+        .ok_or_else(|| eyre::eyre!("Usage: pq upload <location>"))?;
+    let rewards_claimed = read_reward_json(JSON_FILE_PATH)?;
+    let metadata = Metadata {
+        description: String::from("Rewards coordinator claims"),
+        chain_id: config.chain_id,
+        contract: config.contract.clone(),
+        source_sha256: Some(metadata::source_sha256(JSON_FILE_PATH)?),
+        ..Default::default()
+    };
+    let (store, path) = storage::object_store(&location)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let upload_instant = std::time::Instant::now();
+    let metadata = runtime.block_on(storage::put_claims(
+        store.as_ref(),
+        &path,
+        &rewards_claimed,
+        config,
+        metadata,
+    ))?;
+    println!(
+        "Upload time: {:?} us, {} rows to {}",
+        upload_instant.elapsed().as_micros(),
+        metadata.row_count,
+        location
+    );
+    Ok(())
+}
+
+/// `pq stat <location>`: claimed amount and fee statistics of a file, or of every file under a
+/// directory or prefix, fetching only the `claimedAmount` and `feePaid` columns.
+fn stat() -> eyre::Result<()> {
+    let location = std::env::args()
+        .nth(2)
+        .ok_or_else(|| eyre::eyre!("Usage: pq stat <location>"))?;
+    let (store, path) = storage::object_store(&location)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let stat_instant = std::time::Instant::now();
+    let (stat, cost_stat) = runtime.block_on(async {
+        match path.extension() {
+            Some("parquet") => Ok::<_, eyre::Report>((
+                storage::read_reward_stat(store.clone(), &path).await?,
+                storage::read_claim_cost_stat(store, &path).await?,
+            )),
+            _ => Ok((
+                storage::read_dataset_stat(store.clone(), &path).await?,
+                storage::read_dataset_claim_cost_stat(store, &path).await?,
+            )),
+        }
+    })?;
+    println!("Stat time: {:?} us", stat_instant.elapsed().as_micros());
+    println!("Claimed amount stat: {:?}", stat);
+    println!("Claim cost stat: {:?}", cost_stat);
+    Ok(())
+}
+
+/// `pq inspect [parquet]`: schema, row groups, column chunk statistics and footer metadata.
+fn inspect() -> eyre::Result<()> {
+    let path = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| PARQUET_FILE_PATH.to_string());
+    print!("{}", inspect::inspect(&path)?);
+    Ok(())
+}
+
+/// `pq query "<sql>" [--format table|csv|json] [path]`: run SQL over the `claims` table, the
+/// Parquet file or directory at `path`.
+#[cfg(feature = "sql")]
+fn sql_query() -> eyre::Result<()> {
+    use pq::sql::{self, OutputFormat};
+
+    let usage = || eyre::eyre!("Usage: pq query \"<sql>\" [--format table|csv|json] [path]");
+    let mut args = std::env::args().skip(2);
+    let statement = args.next().ok_or_else(usage)?;
+    let mut format = OutputFormat::Table;
+    let mut path = PARQUET_FILE_PATH.to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or_else(usage)?.parse()?,
+            _ => path = arg,
+        }
+    }
+    let runtime = tokio::runtime::Runtime::new()?;
+    let batches = runtime.block_on(sql::query(&path, &statement))?;
+    print!("{}", sql::format_batches(&batches, format)?);
     Ok(())
 }
 
@@ -54,3 +504,17 @@ fn read_reward_json(path: &str) -> eyre::Result<Vec<eigen_types::RewardsClaimed>
     println!("{:?}", rewards_claimed.len());
     Ok(rewards_claimed)
 }
+
+/// The submissions in `path`, or `None` when it is missing, as the indexer only writes it when
+/// no sinks are configured.
+fn read_submission_json(path: &str) -> eyre::Result<Option<Vec<eigen_types::RewardsSubmission>>> {
+    let rewards_submissions = match std::fs::read_to_string(path) {
+        Ok(rewards_submissions) => rewards_submissions,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let rewards_submissions: Vec<eigen_types::RewardsSubmission> =
+        serde_json::from_str(&rewards_submissions)?;
+    println!("{:?}", rewards_submissions.len());
+    Ok(Some(rewards_submissions))
+}
@@ -1,10 +1,103 @@
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+use sha2::{Digest, Sha256};
+use std::{fs::File, io, path::Path};
 
-#[derive(Debug, Serialize, Deserialize)]
+use arrow::array::{Array, UInt64Array};
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ProjectionMask},
+    file::metadata::KeyValue,
+};
+
+use crate::parquet::column_index;
+
+/// The version of the claims table layout, bumped whenever the schema changes.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The prefix of the footer key-value metadata keys written by [`Metadata`].
+const KEY_PREFIX: &str = "pq.";
+
+/// Describes a claims file.  It is stored in the Parquet footer key-value metadata and
+/// optionally mirrored to a JSON sidecar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Metadata {
     pub description: String,
     pub row_count: u64,
+    pub schema_version: u32,
+    pub chain_id: Option<u64>,
+    /// The checksummed address of the contract the claims were indexed from.
+    pub contract: Option<String>,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+    /// The hex SHA-256 of the file the claims were converted from.
+    pub source_sha256: Option<String>,
+    pub writer_version: String,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Metadata {
+            description: String::new(),
+            row_count: 0,
+            schema_version: SCHEMA_VERSION,
+            chain_id: None,
+            contract: None,
+            first_block: None,
+            last_block: None,
+            source_sha256: None,
+            writer_version: writer_version(),
+        }
+    }
+}
+
+/// The `pq` version, recorded in every file written.
+pub fn writer_version() -> String {
+    format!("pq {}", env!("CARGO_PKG_VERSION"))
+}
+
+impl Metadata {
+    /// The footer key-value pairs.  Absent optional fields are left out.
+    pub fn to_key_value_metadata(&self) -> Vec<KeyValue> {
+        let optional = [
+            ("chain_id", self.chain_id.map(|v| v.to_string())),
+            ("contract", self.contract.clone()),
+            ("first_block", self.first_block.map(|v| v.to_string())),
+            ("last_block", self.last_block.map(|v| v.to_string())),
+            ("source_sha256", self.source_sha256.clone()),
+        ];
+        [
+            ("description", Some(self.description.clone())),
+            ("row_count", Some(self.row_count.to_string())),
+            ("schema_version", Some(self.schema_version.to_string())),
+            ("writer_version", Some(self.writer_version.clone())),
+        ]
+        .into_iter()
+        .chain(optional)
+        .filter_map(|(key, value)| Some(KeyValue::new(format!("{}{}", KEY_PREFIX, key), value?)))
+        .collect()
+    }
+
+    /// Parse the footer key-value pairs written by [`Metadata::to_key_value_metadata`].  Keys
+    /// without the `pq.` prefix are ignored.
+    pub fn from_key_value_metadata(key_value_metadata: &[KeyValue]) -> eyre::Result<Self> {
+        let value = |key: &str| {
+            key_value_metadata
+                .iter()
+                .find(|kv| kv.key.strip_prefix(KEY_PREFIX) == Some(key))
+                .and_then(|kv| kv.value.clone())
+        };
+        let required = |key: &str| value(key).ok_or_else(|| eyre::eyre!("No {} in footer", key));
+        Ok(Metadata {
+            description: required("description")?,
+            row_count: required("row_count")?.parse()?,
+            schema_version: required("schema_version")?.parse()?,
+            chain_id: value("chain_id").map(|v| v.parse()).transpose()?,
+            contract: value("contract"),
+            first_block: value("first_block").map(|v| v.parse()).transpose()?,
+            last_block: value("last_block").map(|v| v.parse()).transpose()?,
+            source_sha256: value("source_sha256"),
+            writer_version: required("writer_version")?,
+        })
+    }
 }
 
 pub fn write_metadata_sidecar(
@@ -15,3 +108,268 @@ pub fn write_metadata_sidecar(
     serde_json::to_writer_pretty(file, &metadata)?;
     Ok(())
 }
+
+/// The sidecar of a claims file, e.g. `rewards_coordinator_metadata.json` for
+/// `rewards_coordinator.parquet`.
+pub fn sidecar_path(parquet_path: &str) -> String {
+    let stem = parquet_path
+        .strip_suffix(".parquet")
+        .unwrap_or(parquet_path);
+    format!("{}_metadata.json", stem)
+}
+
+pub fn read_metadata_sidecar(file_path: &str) -> eyre::Result<Metadata> {
+    let file = File::open(file_path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// The hex SHA-256 of a file.
+pub fn source_sha256(path: &str) -> eyre::Result<String> {
+    let mut file = File::open(Path::new(path))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(alloy::primitives::hex::encode(hasher.finalize()))
+}
+
+/// Read the metadata from the footer of a claims file.
+pub fn read_footer_metadata(path: &str) -> eyre::Result<Metadata> {
+    read_footer_metadata_if_present(path)?
+        .ok_or_else(|| eyre::eyre!("No {} key-value metadata in {}", KEY_PREFIX, path))
+}
+
+/// Read the metadata from the footer of a claims file, or `None` for a file written before
+/// the metadata was embedded, which has no `pq.` keys.  Incomplete or invalid metadata is an
+/// error.
+pub fn read_footer_metadata_if_present(path: &str) -> eyre::Result<Option<Metadata>> {
+    let file = File::open(Path::new(path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let Some(key_value_metadata) = builder.metadata().file_metadata().key_value_metadata() else {
+        return Ok(None);
+    };
+    if !key_value_metadata
+        .iter()
+        .any(|kv| kv.key.starts_with(KEY_PREFIX))
+    {
+        return Ok(None);
+    }
+    Metadata::from_key_value_metadata(key_value_metadata).map(Some)
+}
+
+/// Check that the footer metadata describes the rows actually in the file, and that the sidecar
+/// and the source file, when given, agree with it.  Returns the footer metadata.
+pub fn verify(
+    parquet_path: &str,
+    sidecar_path: Option<&str>,
+    source_path: Option<&str>,
+) -> eyre::Result<Metadata> {
+    let footer = read_footer_metadata(parquet_path)?;
+
+    let file = File::open(Path::new(parquet_path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let row_group_rows = builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|rg| rg.num_rows() as u64)
+        .sum::<u64>();
+    let block_number_column = column_index(builder.metadata(), "blockNumber")?;
+    let projection = ProjectionMask::leaves(builder.parquet_schema(), [block_number_column]);
+    let mut rows = 0;
+    let mut first_block = None;
+    let mut last_block = None;
+    for batch in builder.with_projection(projection).build()? {
+        let batch = batch?;
+        let block_numbers = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(|| eyre::eyre!("Unexpected type for column blockNumber"))?;
+        rows += block_numbers.len() as u64;
+        if let Some(&min) = block_numbers.values().iter().min() {
+            first_block = Some(first_block.map_or(min, |first: u64| first.min(min)));
+        }
+        last_block = last_block.max(block_numbers.values().iter().max().copied());
+    }
+
+    if footer.row_count != row_group_rows || footer.row_count != rows {
+        return Err(eyre::eyre!(
+            "Footer row count {} does not match the {} rows in row groups and {} rows read",
+            footer.row_count,
+            row_group_rows,
+            rows
+        ));
+    }
+    if (footer.first_block, footer.last_block) != (first_block, last_block) {
+        return Err(eyre::eyre!(
+            "Footer block range {:?}..={:?} does not match the rows read {:?}..={:?}",
+            footer.first_block,
+            footer.last_block,
+            first_block,
+            last_block
+        ));
+    }
+    // version 1 files only lack the nullable cost columns, which read as no cost
+    if !(1..=SCHEMA_VERSION).contains(&footer.schema_version) {
+        return Err(eyre::eyre!(
+            "Unsupported schema version {}, expected at most {}",
+            footer.schema_version,
+            SCHEMA_VERSION
+        ));
+    }
+    if let Some(sidecar_path) = sidecar_path {
+        let sidecar = read_metadata_sidecar(sidecar_path)?;
+        if sidecar != footer {
+            return Err(eyre::eyre!(
+                "Sidecar {} does not match the footer: {:?} != {:?}",
+                sidecar_path,
+                sidecar,
+                footer
+            ));
+        }
+    }
+    if let Some(source_path) = source_path {
+        let source_sha256 = source_sha256(source_path)?;
+        if footer.source_sha256.as_ref() != Some(&source_sha256) {
+            return Err(eyre::eyre!(
+                "Source {} hash {} does not match the footer {:?}",
+                source_path,
+                source_sha256,
+                footer.source_sha256
+            ));
+        }
+    }
+    Ok(footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parquet::ClaimsWriter;
+
+    fn claim(block_number: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            claimer: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            recipient: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            token: "0x3B78576F7D6837500bA3De27A60c7f594934027E".to_string(),
+            claimed_amount: 1,
+            block_number,
+            transaction_hash: [0; 32],
+            log_index: 0,
+            cost: None,
+        }
+    }
+
+    fn write_claims(name: &str, metadata: Metadata) -> (String, Metadata) {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap().to_string();
+        let mut writer = ClaimsWriter::create(&path, &Config::default())
+            .unwrap()
+            .with_metadata(metadata);
+        writer.write(&[claim(30), claim(10)]).unwrap();
+        writer.write(&[claim(20)]).unwrap();
+        let metadata = writer.close().unwrap();
+        (path, metadata)
+    }
+
+    #[test]
+    fn test_key_value_round_trip() {
+        let metadata = Metadata {
+            description: "claims".to_string(),
+            row_count: 3,
+            chain_id: Some(17000),
+            contract: Some("0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string()),
+            first_block: Some(1),
+            last_block: Some(2),
+            source_sha256: Some("00".repeat(32)),
+            ..Default::default()
+        };
+        let key_value_metadata = metadata.to_key_value_metadata();
+        assert!(key_value_metadata
+            .iter()
+            .all(|kv| kv.key.starts_with("pq.")));
+        assert_eq!(
+            Metadata::from_key_value_metadata(&key_value_metadata).unwrap(),
+            metadata
+        );
+
+        let metadata = Metadata::default();
+        assert_eq!(metadata.to_key_value_metadata().len(), 4);
+        assert_eq!(
+            Metadata::from_key_value_metadata(&metadata.to_key_value_metadata()).unwrap(),
+            metadata
+        );
+        assert!(Metadata::from_key_value_metadata(&[]).is_err());
+    }
+
+    #[test]
+    fn test_footer_metadata_written() {
+        let (path, metadata) = write_claims(
+            "pq_test_footer_metadata.parquet",
+            Metadata {
+                description: "claims".to_string(),
+                chain_id: Some(17000),
+                ..Default::default()
+            },
+        );
+        assert_eq!(metadata.row_count, 3);
+        assert_eq!(metadata.first_block, Some(10));
+        assert_eq!(metadata.last_block, Some(30));
+        assert_eq!(metadata.schema_version, SCHEMA_VERSION);
+        assert_eq!(metadata.writer_version, writer_version());
+        assert_eq!(read_footer_metadata(&path).unwrap(), metadata);
+        assert_eq!(verify(&path, None, None).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_verify_sidecar_and_source() {
+        let source = std::env::temp_dir().join("pq_test_verify_source.json");
+        let source = source.to_str().unwrap();
+        std::fs::write(source, "[]").unwrap();
+        let (path, metadata) = write_claims(
+            "pq_test_verify.parquet",
+            Metadata {
+                source_sha256: Some(source_sha256(source).unwrap()),
+                ..Default::default()
+            },
+        );
+        let sidecar = std::env::temp_dir().join("pq_test_verify_metadata.json");
+        let sidecar = sidecar.to_str().unwrap();
+        write_metadata_sidecar(sidecar, &metadata).unwrap();
+        assert!(verify(&path, Some(sidecar), Some(source)).is_ok());
+
+        // the sidecar drifted from the data
+        let drifted = Metadata {
+            row_count: 4,
+            ..metadata.clone()
+        };
+        write_metadata_sidecar(sidecar, &drifted).unwrap();
+        assert!(verify(&path, Some(sidecar), None).is_err());
+
+        // the source changed since the conversion
+        std::fs::write(source, "[{}]").unwrap();
+        assert!(verify(&path, None, Some(source)).is_err());
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            sidecar_path("rewards_coordinator.parquet"),
+            "rewards_coordinator_metadata.json"
+        );
+        assert_eq!(sidecar_path("claims"), "claims_metadata.json");
+    }
+
+    #[test]
+    fn test_source_sha256() {
+        let path = std::env::temp_dir().join("pq_test_source_sha256.json");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "abc").unwrap();
+        assert_eq!(
+            source_sha256(path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -1,256 +1,851 @@
-use std::{fs, path::Path, sync::Arc};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
-use alloy::sol_types::SolValue;
-use eyre::Ok;
+use alloy::primitives::{Address, U256};
 use parquet::{
-    column::reader::ColumnReader, data_type::FixedLenByteArray, data_type::FixedLenByteArrayType,
-    file::reader::FileReader, file::reader::SerializedFileReader,
-    file::writer::SerializedFileWriter, schema::parser::parse_message_type,
+    arrow::{
+        arrow_reader::{
+            ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+        },
+        ArrowWriter, ProjectionMask,
+    },
+    column::reader::{get_typed_column_reader, ColumnReader},
+    data_type::{DataType, FixedLenByteArray, FixedLenByteArrayType},
+    file::metadata::ParquetMetaData,
+    file::reader::SerializedFileReader,
+    file::reader::{ChunkReader, FileReader},
+    file::writer::SerializedRowGroupWriter,
+    record::{reader::RowIter, Field, Row, RowAccessor},
 };
 use tracing::debug;
 
-#[derive(Debug)]
+use crate::cluster;
+use crate::config::Config;
+use crate::metadata::{writer_version, Metadata, SCHEMA_VERSION};
+use crate::record_batch;
+
+/// Width of the binary address columns.
+const ADDRESS_LEN: usize = 20;
+/// Width of the `DECIMAL(76,0)` amount column.
+const AMOUNT_LEN: usize = 32;
+
+/// Claimed amount statistics in the token's base units.  The mean is rounded down.
+#[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
 pub struct ClaimedAmountStat {
-    pub sum: u128,
+    pub sum: U256,
     pub count: u64,
-    pub claimed_mean: f64,
-    pub claimed_max: u128,
-    pub claimed_min: u128,
+    pub claimed_mean: U256,
+    pub claimed_max: U256,
+    pub claimed_min: U256,
+}
+
+impl Default for ClaimedAmountStat {
+    fn default() -> Self {
+        ClaimedAmountStat {
+            sum: U256::ZERO,
+            count: 0,
+            claimed_mean: U256::ZERO,
+            claimed_max: U256::ZERO,
+            claimed_min: U256::MAX,
+        }
+    }
+}
+
+impl ClaimedAmountStat {
+    /// Combine the statistics of two disjoint sets of claims.
+    pub fn merge(&mut self, other: &ClaimedAmountStat) {
+        self.sum += other.sum;
+        self.count += other.count;
+        self.claimed_max = self.claimed_max.max(other.claimed_max);
+        self.claimed_min = self.claimed_min.min(other.claimed_min);
+        if self.count > 0 {
+            self.claimed_mean = self.sum / U256::from(self.count);
+        }
+    }
+}
+
+pub(crate) fn amount_to_fixed_bytes(amount: u128) -> FixedLenByteArray {
+    FixedLenByteArray::from(U256::from(amount).to_be_bytes::<AMOUNT_LEN>().to_vec())
+}
+
+/// Convert a stored `DECIMAL(76,0)` value back into an amount.
+pub fn amount_from_bytes(data: &[u8]) -> eyre::Result<U256> {
+    if data.len() != AMOUNT_LEN {
+        return Err(eyre::eyre!("Invalid amount length: {}", data.len()));
+    }
+    Ok(U256::from_be_slice(data))
+}
+
+/// Parse an address for storage.  Mixed-case addresses must carry a valid EIP-55 checksum.
+pub(crate) fn parse_address(address: &str) -> eyre::Result<Address> {
+    let parsed = Address::from_str(address)?;
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && parsed.to_checksum(None) != address {
+        return Err(eyre::eyre!("Invalid address checksum: {}", address));
+    }
+    Ok(parsed)
+}
+
+/// Convert a stored address column value back into a checksummed address.
+pub fn address_from_bytes(data: &[u8]) -> eyre::Result<String> {
+    if data.len() != ADDRESS_LEN {
+        return Err(eyre::eyre!("Invalid address length: {}", data.len()));
+    }
+    Ok(Address::from_slice(data).to_checksum(None))
+}
+
+pub(crate) fn write_column<T: DataType>(
+    row_group_writer: &mut SerializedRowGroupWriter<'_, fs::File>,
+    values: &[T::T],
+) -> eyre::Result<()> {
+    if let Some(mut col_writer) = row_group_writer.next_column()? {
+        col_writer.typed::<T>().write_batch(values, None, None)?;
+        col_writer.close()?;
+    }
+    Ok(())
+}
+
+/// Row groups are flushed once they hold this many rows.
+pub const DEFAULT_MAX_ROW_GROUP_ROWS: usize = 1024 * 1024;
+/// Row groups are flushed once their buffered size reaches this many bytes.
+pub const DEFAULT_MAX_ROW_GROUP_BYTES: usize = 128 * 1024 * 1024;
+/// Values decoded per column reader call.
+pub(crate) const READ_BATCH_SIZE: usize = 8192;
+
+/// Streams claims into a Parquet file with the [`record_batch::claims_schema`] layout, starting
+/// a new row group whenever the current one reaches the configured row count or buffered size.
+/// The row count and block range are tracked for the [`Metadata`] written into the footer.
+///
+/// When the config sets `sortKeys` or `zOrder` the rows are held until [`ClaimsWriter::close`]
+/// and written in that order.
+pub struct ClaimsWriter<W: Write + Send = fs::File> {
+    writer: ArrowWriter<W>,
+    config: Config,
+    metadata: Metadata,
+    /// Rows waiting to be clustered.
+    pending: Vec<eigen_types::RewardsClaimed>,
+    /// The temporary file being written and the path it is renamed to on close.
+    rename: Option<(PathBuf, PathBuf)>,
 }
 
-fn convert_to_fixed_bytes(data: Vec<u8>) -> eyre::Result<FixedLenByteArray> {
-    let fixed_bytes = FixedLenByteArray::from(data);
-    Ok(fixed_bytes)
+impl ClaimsWriter {
+    /// Write the file at `path`.  The rows go to `<path>.tmp`, which is renamed over `path` on
+    /// close, so an existing file is never modified in place and readers, including memory
+    /// maps, see either the old or the new file.
+    pub fn create(path: &str, config: &Config) -> eyre::Result<Self> {
+        let path = PathBuf::from(path);
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        let mut writer = ClaimsWriter::new(fs::File::create(&tmp)?, config)?;
+        writer.rename = Some((tmp, path));
+        Ok(writer)
+    }
 }
 
+impl<W: Write + Send> ClaimsWriter<W> {
+    /// Write to any sink, e.g. a `Vec<u8>` to be uploaded to an object store.
+    pub fn new(writer: W, config: &Config) -> eyre::Result<Self> {
+        let properties = config.writer_properties()?;
+        let writer = ArrowWriter::try_new(writer, record_batch::claims_schema(), Some(properties))?;
+        Ok(ClaimsWriter {
+            writer,
+            config: config.clone(),
+            metadata: Metadata::default(),
+            pending: Vec::new(),
+            rename: None,
+        })
+    }
+
+    /// Describe the file with `metadata`.  The row count, block range, schema and writer
+    /// versions are filled in by the writer.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Metadata {
+            row_count: self.metadata.row_count,
+            first_block: self.metadata.first_block,
+            last_block: self.metadata.last_block,
+            ..metadata
+        };
+        self
+    }
+
+    pub fn write(&mut self, rewards_claimed: &[eigen_types::RewardsClaimed]) -> eyre::Result<()> {
+        self.metadata.row_count += rewards_claimed.len() as u64;
+        for claim in rewards_claimed {
+            let first_block = self.metadata.first_block.get_or_insert(claim.block_number);
+            *first_block = (*first_block).min(claim.block_number);
+            let last_block = self.metadata.last_block.get_or_insert(claim.block_number);
+            *last_block = (*last_block).max(claim.block_number);
+        }
+        if self.config.clustered() {
+            self.pending.extend_from_slice(rewards_claimed);
+            return Ok(());
+        }
+        self.write_batch(rewards_claimed)
+    }
+
+    fn write_batch(&mut self, rewards_claimed: &[eigen_types::RewardsClaimed]) -> eyre::Result<()> {
+        self.writer
+            .write(&record_batch::to_record_batch(rewards_claimed)?)?;
+        if self.writer.in_progress_size() >= self.config.max_row_group_bytes {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush the last row group and write the footer with the file's [`Metadata`], which is
+    /// returned for mirroring to a sidecar.
+    pub fn close(self) -> eyre::Result<Metadata> {
+        Ok(self.into_inner()?.1)
+    }
+
+    /// Like [`ClaimsWriter::close`], also returning the underlying writer.
+    pub fn into_inner(mut self) -> eyre::Result<(W, Metadata)> {
+        let pending = cluster::cluster(std::mem::take(&mut self.pending), &self.config)?;
+        for chunk in pending.chunks(READ_BATCH_SIZE) {
+            self.write_batch(chunk)?;
+        }
+        let metadata = Metadata {
+            schema_version: SCHEMA_VERSION,
+            writer_version: writer_version(),
+            ..self.metadata
+        };
+        for key_value in metadata.to_key_value_metadata() {
+            self.writer.append_key_value_metadata(key_value);
+        }
+        let mut writer = self.writer.into_inner()?;
+        if let Some((tmp, path)) = self.rename {
+            writer.flush()?;
+            fs::rename(tmp, path)?;
+        }
+        Ok((writer, metadata))
+    }
+}
+
+/// Write the claims with the [`record_batch::claims_schema`] layout and default writer options.
+///
+/// `root` is the 32 byte merkle root.  `earner`, `claimer`, `recipient` and `token` are the
+/// 20 byte binary addresses with no logical type annotation; readers restore the checksummed
+/// form with [`address_from_bytes`].  `claimedAmount` is a `DECIMAL(76,0)` stored as a 32 byte
+/// big-endian two's complement integer, which holds any `uint256` below 10^76.  `blockNumber`,
+/// `transactionHash` and `logIndex` identify the log the claim was decoded from.
 pub fn write_parquet_file(
     path: &str,
     rewards_claimed: &[eigen_types::RewardsClaimed],
 ) -> eyre::Result<()> {
-    let path = Path::new(path);
-    let message_type = "
-      message schema {
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) root;
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) earner;
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) claimer;
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) recipient;
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) token;
-        REQUIRED FIXED_LEN_BYTE_ARRAY (32) claimedAmount;
-      }
-    ";
-
-    let schema = Arc::new(parse_message_type(message_type).unwrap());
-    let file = fs::File::create(path).unwrap();
-    let mut writer = SerializedFileWriter::new(file, schema, Default::default()).unwrap();
-    let mut row_group_writer = writer.next_row_group().unwrap();
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // roots
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.root.clone().to_vec()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
-    }
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // earners
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.earner.clone().into()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
-    }
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // claimer
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.claimer.clone().into()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
-    }
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // claimer
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.recipient.clone().into()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
-    }
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // claimer
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.token.clone().into()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
-    }
-    if let Some(mut col_writer) = row_group_writer.next_column().unwrap() {
-        // claimer
-        col_writer
-            .typed::<FixedLenByteArrayType>()
-            .write_batch(
-                &rewards_claimed
-                    .iter()
-                    .map(|r| convert_to_fixed_bytes(r.claimed_amount.abi_encode()).unwrap())
-                    .collect::<Vec<FixedLenByteArray>>(),
-                None,
-                None,
-            )
-            .unwrap();
-        col_writer.close().unwrap();
+    let mut writer = ClaimsWriter::create(path, &Config::default())?;
+    writer.write(rewards_claimed)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Read the claims as Arrow record batches of `batch_size` rows.
+pub fn read_record_batches(
+    path: &str,
+    batch_size: usize,
+) -> eyre::Result<ParquetRecordBatchReader> {
+    let file = fs::File::open(Path::new(path))?;
+    Ok(ParquetRecordBatchReaderBuilder::try_new(file)?
+        .with_batch_size(batch_size)
+        .build()?)
+}
+
+/// Claimed amount statistics over the whole file using the Arrow aggregate kernels.  Only the
+/// `claimedAmount` column is decoded.
+pub fn read_reward_file_stat_arrow(path: &str) -> eyre::Result<ClaimedAmountStat> {
+    read_chunk_stat(fs::File::open(Path::new(path))?)
+}
+
+/// Claimed amount statistics of a claims file held by any [`ChunkReader`].
+pub(crate) fn read_chunk_stat<T: ChunkReader + 'static>(
+    reader: T,
+) -> eyre::Result<ClaimedAmountStat> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(reader)?;
+    let claimed_amount_column = column_index(builder.metadata(), "claimedAmount")?;
+    let projection = ProjectionMask::leaves(builder.parquet_schema(), [claimed_amount_column]);
+    let reader = builder.with_projection(projection).build()?;
+    let mut claimed_stat = ClaimedAmountStat::default();
+    for batch in reader {
+        claimed_stat.merge(&record_batch::claimed_amount_stat(&batch?)?);
     }
+    Ok(claimed_stat)
+}
 
-    row_group_writer.close().unwrap();
-    writer.close().unwrap();
+/// Fee statistics of the claims with a cost in the file.  Only the `claimedAmount` and `feePaid`
+/// columns are decoded; files written before costs were recorded have none.
+pub fn read_claim_cost_stat(path: &str) -> eyre::Result<eigen_types::ClaimCostStat> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(Path::new(path))?)?;
+    if builder.schema().column_with_name("feePaid").is_none() {
+        return Ok(eigen_types::ClaimCostStat::default());
+    }
+    let columns = [
+        column_index(builder.metadata(), "claimedAmount")?,
+        column_index(builder.metadata(), "feePaid")?,
+    ];
+    let projection = ProjectionMask::leaves(builder.parquet_schema(), columns);
+    let mut fees = Vec::new();
+    for batch in builder.with_projection(projection).build()? {
+        fees.extend(record_batch::claim_fees(&batch?)?);
+    }
+    Ok(eigen_types::ClaimCostStat::from_fees(fees))
+}
 
-    Ok(())
+/// Claimed amount statistics over the whole file, decoding row groups on `threads` worker
+/// threads.  Each worker opens its own handle on the file, takes the next unread row group until
+/// none are left and merges it into a partial result; the partials are merged at the end.
+pub fn read_reward_file_stat_parallel(
+    path: &str,
+    threads: usize,
+) -> eyre::Result<ClaimedAmountStat> {
+    if threads == 0 {
+        return Err(eyre::eyre!("threads must be positive"));
+    }
+    let file = fs::File::open(Path::new(path))?;
+    let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+    let claimed_amount_column = column_index(metadata.metadata(), "claimedAmount")?;
+    let num_row_groups = metadata.metadata().num_row_groups();
+    let next_row_group = AtomicUsize::new(0);
+
+    let read_row_groups = || -> eyre::Result<ClaimedAmountStat> {
+        let mut claimed_stat = ClaimedAmountStat::default();
+        loop {
+            let row_group = next_row_group.fetch_add(1, Ordering::Relaxed);
+            if row_group >= num_row_groups {
+                return Ok(claimed_stat);
+            }
+            let file = fs::File::open(Path::new(path))?;
+            let builder =
+                ParquetRecordBatchReaderBuilder::new_with_metadata(file, metadata.clone());
+            let projection =
+                ProjectionMask::leaves(builder.parquet_schema(), [claimed_amount_column]);
+            let reader = builder
+                .with_projection(projection)
+                .with_row_groups(vec![row_group])
+                .build()?;
+            for batch in reader {
+                claimed_stat.merge(&record_batch::claimed_amount_stat(&batch?)?);
+            }
+        }
+    };
+
+    thread::scope(|scope| {
+        let workers = (0..threads.min(num_row_groups))
+            .map(|_| scope.spawn(read_row_groups))
+            .collect::<Vec<_>>();
+        let mut claimed_stat = ClaimedAmountStat::default();
+        for worker in workers {
+            let partial = worker
+                .join()
+                .map_err(|_| eyre::eyre!("Row group reader panicked"))??;
+            claimed_stat.merge(&partial);
+        }
+        Ok(claimed_stat)
+    })
 }
 
-#[allow(dead_code)] // this function is an example for testing purposes
-pub fn read_reward_file(path: &str) -> eyre::Result<()> {
-    let path = Path::new(path);
-    // Reading data using column reader API.
-    let file = fs::File::open(path).unwrap();
-    let reader = SerializedFileReader::new(file).unwrap();
-    let metadata = reader.metadata();
+pub(crate) fn column_index(metadata: &ParquetMetaData, column: &str) -> eyre::Result<usize> {
+    metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .position(|c| c.name() == column)
+        .ok_or_else(|| eyre::eyre!("No column named {}", column))
+}
 
+/// Read an address column from every row group as checksummed addresses.
+pub fn read_address_column(path: &str, column: &str) -> eyre::Result<Vec<String>> {
+    let file = fs::File::open(Path::new(path))?;
+    let reader = SerializedFileReader::new(file)?;
+    let metadata = reader.metadata();
+    let column_index = column_index(metadata, column)?;
+    let mut addresses = Vec::new();
     for i in 0..metadata.num_row_groups() {
-        let row_group_reader = reader.get_row_group(i).unwrap();
-        let row_group_metadata = metadata.row_group(i);
-        println!(
-            "Row group: {} has {} rows",
-            i,
-            row_group_metadata.num_rows()
-        );
-        println!(
-            "Row group: {} has {} columns",
-            i,
-            row_group_metadata.num_columns()
+        let row_group_reader = reader.get_row_group(i)?;
+        let mut column_reader = get_typed_column_reader::<FixedLenByteArrayType>(
+            row_group_reader.get_column_reader(column_index)?,
         );
-        for j in 0..row_group_metadata.num_columns() {
-            let mut column_reader = row_group_reader.get_column_reader(j).unwrap();
-            match column_reader {
-                // You can also use `get_typed_column_reader` method to extract typed reader.
-                ColumnReader::FixedLenByteArrayColumnReader(ref mut dx_reader) => {
-                    let mut values = vec![];
-                    let mut def_levels = vec![];
-                    let mut rep_levels = vec![];
-
-                    let (records_read, values_read, levels_read) = dx_reader
-                        .read_records(
-                            10000, // maximum records to read
-                            Some(&mut def_levels),
-                            Some(&mut rep_levels),
-                            &mut values,
-                        )
-                        .unwrap();
-                    println!("Read {} records", records_read);
-                    println!("Values read: {:?}", values_read);
-                    println!("Def levels read: {:?}", levels_read);
-                    println!("Values are: {:?}", values);
-                }
-                _ => {
-                    println!("Column type not supported");
-                }
+        loop {
+            let mut values = Vec::with_capacity(READ_BATCH_SIZE);
+            let (records_read, _, _) =
+                column_reader.read_records(READ_BATCH_SIZE, None, None, &mut values)?;
+            if records_read == 0 {
+                break;
+            }
+            for value in values {
+                addresses.push(address_from_bytes(value.data())?);
             }
         }
     }
-    Ok(())
+    Ok(addresses)
+}
+
+/// Streams the claims out of a Parquet file one row at a time.
+pub struct RewardsClaimedIter {
+    rows: RowIter<'static>,
+}
+
+impl RewardsClaimedIter {
+    pub fn open(path: &str) -> eyre::Result<Self> {
+        let file = fs::File::open(Path::new(path))?;
+        let reader = SerializedFileReader::new(file)?;
+        Ok(RewardsClaimedIter {
+            rows: RowIter::from_file_into(Box::new(reader)),
+        })
+    }
+}
+
+impl Iterator for RewardsClaimedIter {
+    type Item = eyre::Result<eigen_types::RewardsClaimed>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.rows.next()?;
+        Some(
+            row.map_err(Into::into)
+                .and_then(|row| to_rewards_claimed(&row)),
+        )
+    }
+}
+
+fn to_rewards_claimed(row: &Row) -> eyre::Result<eigen_types::RewardsClaimed> {
+    let claimed_amount = amount_from_bytes(row.get_decimal(5)?.data())?;
+    Ok(eigen_types::RewardsClaimed {
+        root: row.get_bytes(0)?.data().try_into()?,
+        earner: address_from_bytes(row.get_bytes(1)?.data())?,
+        claimer: address_from_bytes(row.get_bytes(2)?.data())?,
+        recipient: address_from_bytes(row.get_bytes(3)?.data())?,
+        token: address_from_bytes(row.get_bytes(4)?.data())?,
+        claimed_amount: claimed_amount.try_into()?,
+        block_number: row.get_ulong(6)?,
+        transaction_hash: row.get_bytes(7)?.data().try_into()?,
+        log_index: row.get_ulong(8)?,
+        cost: to_claim_cost(row)?,
+    })
+}
+
+/// The cost columns follow `logIndex`.  Files written before schema version 2 end there, and
+/// claims without a cost hold nulls.
+fn to_claim_cost(row: &Row) -> eyre::Result<Option<eigen_types::ClaimCost>> {
+    if row.len() < 12
+        || row
+            .get_column_iter()
+            .skip(9)
+            .any(|(_, f)| *f == Field::Null)
+    {
+        return Ok(None);
+    }
+    Ok(Some(eigen_types::ClaimCost {
+        gas_used: row.get_ulong(9)?,
+        effective_gas_price: amount_from_bytes(row.get_decimal(10)?.data())?.try_into()?,
+        fee_paid: amount_from_bytes(row.get_decimal(11)?.data())?.try_into()?,
+    }))
+}
+
+/// Read every claim in the file.
+pub fn read_reward_file(path: &str) -> eyre::Result<Vec<eigen_types::RewardsClaimed>> {
+    RewardsClaimedIter::open(path)?.collect()
 }
 
 pub fn read_reward_file_stat(path: &str) -> eyre::Result<ClaimedAmountStat> {
     let path = Path::new(path);
     // Reading data using column reader API.
-    let file = fs::File::open(path).unwrap();
-    let reader = SerializedFileReader::new(file).unwrap();
+    let file = fs::File::open(path)?;
+    let reader = SerializedFileReader::new(file)?;
     let metadata = reader.metadata();
-    let mut claimed_stat = ClaimedAmountStat {
-        sum: 0,
-        count: 0,
-        claimed_mean: 0.0,
-        claimed_max: 0,
-        claimed_min: u128::MAX,
-    };
+    let mut claimed_stat = ClaimedAmountStat::default();
+    let claimed_amount_column = column_index(metadata, "claimedAmount")?;
     for i in 0..metadata.num_row_groups() {
-        let row_group_reader = reader.get_row_group(i).unwrap();
-        let row_group_metadata = metadata.row_group(i);
-        let claimed_amount_column = row_group_metadata.num_columns() - 1;
-        let mut column_reader = row_group_reader
-            .get_column_reader(claimed_amount_column)
-            .unwrap();
+        let row_group_reader = reader.get_row_group(i)?;
+        let mut column_reader = row_group_reader.get_column_reader(claimed_amount_column)?;
         match column_reader {
             // You can also use `get_typed_column_reader` method to extract typed reader.
-            ColumnReader::FixedLenByteArrayColumnReader(ref mut dx_reader) => {
-                let mut values = vec![];
-                let mut def_levels = vec![];
-                let mut rep_levels = vec![];
-
-                let (records_read, values_read, levels_read) = dx_reader
-                    .read_records(
-                        10000, // maximum records to read
-                        Some(&mut def_levels),
-                        Some(&mut rep_levels),
-                        &mut values,
-                    )
-                    .unwrap();
-                println!("Read {} records", records_read);
-                println!("Values read: {:?}", values_read);
-                println!("Def levels read: {:?}", levels_read);
+            ColumnReader::FixedLenByteArrayColumnReader(ref mut dx_reader) => loop {
+                // continue across pages until the row group is exhausted
+                let mut values = Vec::with_capacity(READ_BATCH_SIZE);
+                let (records_read, _, _) =
+                    dx_reader.read_records(READ_BATCH_SIZE, None, None, &mut values)?;
+                if records_read == 0 {
+                    break;
+                }
+                debug!("Row group {}: read {} records", i, records_read);
                 for value in values {
-                    if value.data().len() != 32 {
-                        debug!("Invalid fixed byte data length: {}", value.data().len());
-                        continue;
-                    }
-                    // last 16 bytes
-                    let data_bytes = value.data()[16..].try_into().unwrap();
-                    let claimed_amount =
-                        u128::from_le_bytes(data_bytes) / 1_000_000_000_000_000_000;
+                    let claimed_amount = amount_from_bytes(value.data())?;
                     claimed_stat.sum += claimed_amount;
                     claimed_stat.count += 1;
-                    claimed_stat.claimed_mean = claimed_stat.sum as f64 / claimed_stat.count as f64;
                     claimed_stat.claimed_max = claimed_amount.max(claimed_stat.claimed_max);
                     claimed_stat.claimed_min = claimed_amount.min(claimed_stat.claimed_min);
                 }
-            }
+            },
             _ => {
-                println!("Column type not supported");
+                return Err(eyre::eyre!(
+                    "Unsupported physical type for claimedAmount in row group {}",
+                    i
+                ))
             }
         }
     }
+    if claimed_stat.count > 0 {
+        claimed_stat.claimed_mean = claimed_stat.sum / U256::from(claimed_stat.count);
+    }
     Ok(claimed_stat)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn claim_amount(claimed_amount: u128) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            claimed_amount,
+            ..claim(
+                "0xAcc1fb458a1317E886dB376Fc8141540537E68fE",
+                "0x3B78576F7D6837500bA3De27A60c7f594934027E",
+            )
+        }
+    }
+
+    fn claim(earner: &str, token: &str) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: earner.to_string(),
+            claimer: earner.to_string(),
+            recipient: earner.to_string(),
+            token: token.to_string(),
+            claimed_amount: 1_000_000_000_000_000_000,
+            block_number: 0,
+            transaction_hash: [0; 32],
+            log_index: 0,
+            cost: None,
+        }
+    }
+
+    #[test]
+    fn test_address_round_trip() {
+        let path = std::env::temp_dir().join("pq_test_address_round_trip.parquet");
+        let path = path.to_str().unwrap();
+        let earner = "0xAcc1fb458a1317E886dB376Fc8141540537E68fE";
+        let token = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+        // lowercase input is read back checksummed
+        let claims = vec![claim(earner, token), claim(&earner.to_lowercase(), token)];
+        write_parquet_file(path, &claims).unwrap();
+
+        assert_eq!(
+            read_address_column(path, "earner").unwrap(),
+            vec![earner, earner]
+        );
+        assert_eq!(
+            read_address_column(path, "token").unwrap(),
+            vec![token, token]
+        );
+
+        let file = fs::File::open(path).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr_ptr();
+        for i in 1..5 {
+            assert_eq!(schema.column(i).type_length(), ADDRESS_LEN as i32);
+        }
+    }
+
+    #[test]
+    fn test_invalid_address_rejected() {
+        let path = std::env::temp_dir().join("pq_test_invalid_address.parquet");
+        let path = path.to_str().unwrap();
+        let token = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+        // bad checksum
+        let claims = vec![claim("0xacc1fb458a1317E886dB376Fc8141540537E68fE", token)];
+        assert!(write_parquet_file(path, &claims).is_err());
+        // too short
+        let claims = vec![claim("0xAcc1fb458a1317E886dB376Fc8141540537E68", token)];
+        assert!(write_parquet_file(path, &claims).is_err());
+    }
+
+    #[test]
+    fn test_address_from_bytes_length() {
+        assert!(address_from_bytes(&[0u8; 32]).is_err());
+        assert_eq!(
+            address_from_bytes(&[0u8; 20]).unwrap(),
+            "0x0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_claimed_amount_stat() {
+        let path = std::env::temp_dir().join("pq_test_claimed_amount_stat.parquet");
+        let path = path.to_str().unwrap();
+        let claims = vec![
+            claim_amount(1_000_000_000_000_000_000),
+            claim_amount(2_500_000_000_000_000_001),
+            claim_amount(u128::MAX),
+        ];
+        write_parquet_file(path, &claims).unwrap();
+
+        let stat = read_reward_file_stat(path).unwrap();
+        let sum = U256::from(3_500_000_000_000_000_001u128) + U256::from(u128::MAX);
+        assert_eq!(stat.count, 3);
+        assert_eq!(stat.sum, sum);
+        assert_eq!(stat.claimed_mean, sum / U256::from(3));
+        assert_eq!(stat.claimed_max, U256::from(u128::MAX));
+        assert_eq!(stat.claimed_min, U256::from(1_000_000_000_000_000_000u128));
+
+        let arrow_stat = read_reward_file_stat_arrow(path).unwrap();
+        assert_eq!(arrow_stat.count, stat.count);
+        assert_eq!(arrow_stat.sum, stat.sum);
+        assert_eq!(arrow_stat.claimed_max, stat.claimed_max);
+        assert_eq!(arrow_stat.claimed_min, stat.claimed_min);
+    }
+
+    #[test]
+    fn test_claimed_amount_decimal_encoding() {
+        let path = std::env::temp_dir().join("pq_test_claimed_amount_decimal.parquet");
+        let path = path.to_str().unwrap();
+        write_parquet_file(path, &[claim_amount(0x0102)]).unwrap();
+
+        let file = fs::File::open(path).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr_ptr();
+        let column = schema.column(5);
+        assert_eq!(column.type_length(), AMOUNT_LEN as i32);
+        assert_eq!(column.type_precision(), 76);
+        assert_eq!(column.type_scale(), 0);
+
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        let decimal = row.get_decimal(5).unwrap();
+        let mut expected = [0u8; AMOUNT_LEN];
+        expected[30] = 0x01;
+        expected[31] = 0x02;
+        assert_eq!(decimal.data(), expected);
+        assert_eq!(
+            amount_from_bytes(decimal.data()).unwrap(),
+            U256::from(0x0102)
+        );
+    }
+
+    /// A file with one `claimedAmount` column holding `values`.
+    fn write_amount_column(name: &str, values: arrow::array::ArrayRef) -> String {
+        let path = std::env::temp_dir().join(name);
+        let batch = arrow::array::RecordBatch::try_from_iter([("claimedAmount", values)]).unwrap();
+        let mut writer =
+            ArrowWriter::try_new(fs::File::create(&path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_claimed_amount_stat_rejects_other_encodings() {
+        // a 16 byte decimal is not skipped
+        let path = write_amount_column(
+            "pq_test_claimed_amount_decimal128.parquet",
+            std::sync::Arc::new(
+                arrow::array::Decimal128Array::from(vec![1, 2])
+                    .with_precision_and_scale(38, 0)
+                    .unwrap(),
+            ),
+        );
+        let error = read_reward_file_stat(&path).unwrap_err().to_string();
+        assert!(error.contains("Invalid amount length: 16"), "{}", error);
+
+        let path = write_amount_column(
+            "pq_test_claimed_amount_int64.parquet",
+            std::sync::Arc::new(arrow::array::Int64Array::from(vec![1, 2])),
+        );
+        let error = read_reward_file_stat(&path).unwrap_err().to_string();
+        assert!(error.contains("Unsupported physical type"), "{}", error);
+    }
+
+    fn arb_address() -> impl Strategy<Value = String> {
+        any::<[u8; 20]>().prop_map(|bytes| Address::from(bytes).to_checksum(None))
+    }
+
+    prop_compose! {
+        fn arb_claim_cost()(
+            gas_used in any::<u64>(),
+            effective_gas_price in any::<u128>(),
+            fee_paid in any::<u128>(),
+        ) -> eigen_types::ClaimCost {
+            eigen_types::ClaimCost {
+                gas_used,
+                effective_gas_price,
+                fee_paid,
+            }
+        }
+    }
+
+    prop_compose! {
+        fn arb_rewards_claimed()(
+            root in any::<[u8; 32]>(),
+            earner in arb_address(),
+            claimer in arb_address(),
+            recipient in arb_address(),
+            token in arb_address(),
+            claimed_amount in any::<u128>(),
+            block_number in any::<u64>(),
+            transaction_hash in any::<[u8; 32]>(),
+            log_index in any::<u64>(),
+            cost in proptest::option::of(arb_claim_cost()),
+        ) -> eigen_types::RewardsClaimed {
+            eigen_types::RewardsClaimed {
+                root,
+                earner,
+                claimer,
+                recipient,
+                token,
+                claimed_amount,
+                block_number,
+                transaction_hash,
+                log_index,
+                cost,
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_json_parquet_round_trip(
+            claims in prop::collection::vec(arb_rewards_claimed(), 0..64)
+        ) {
+            let path = std::env::temp_dir().join("pq_test_json_parquet_round_trip.parquet");
+            let path = path.to_str().unwrap();
+            let json = serde_json::to_string(&claims).unwrap();
+            let from_json: Vec<eigen_types::RewardsClaimed> = serde_json::from_str(&json).unwrap();
+            write_parquet_file(path, &from_json).unwrap();
+
+            prop_assert_eq!(read_reward_file(path).unwrap(), claims);
+        }
+
+        #[test]
+        fn test_streaming_reader_matches(
+            claims in prop::collection::vec(arb_rewards_claimed(), 1..64)
+        ) {
+            let path = std::env::temp_dir().join("pq_test_streaming_reader.parquet");
+            let path = path.to_str().unwrap();
+            write_parquet_file(path, &claims).unwrap();
+
+            let mut iter = RewardsClaimedIter::open(path).unwrap();
+            for claim in &claims {
+                prop_assert_eq!(&iter.next().unwrap().unwrap(), claim);
+            }
+            prop_assert!(iter.next().is_none());
+        }
+    }
+
+    fn synthetic_claim(i: u64) -> eigen_types::RewardsClaimed {
+        let earner = Address::left_padding_from(&(i % 1000).to_be_bytes());
+        let token = Address::left_padding_from(&(i % 7 + 1).to_be_bytes());
+        eigen_types::RewardsClaimed {
+            block_number: i / 10,
+            log_index: i % 10,
+            claimed_amount: i as u128 * 1_000_000_000,
+            ..claim(&earner.to_checksum(None), &token.to_checksum(None))
+        }
+    }
+
+    fn write_synthetic(
+        path: &str,
+        rows: u64,
+        chunk: u64,
+        max_row_group_rows: usize,
+        max_row_group_bytes: usize,
+    ) {
+        let config = Config {
+            max_row_group_rows,
+            max_row_group_bytes,
+            ..Default::default()
+        };
+        let mut writer = ClaimsWriter::create(path, &config).unwrap();
+        for start in (0..rows).step_by(chunk as usize) {
+            let claims = (start..(start + chunk).min(rows))
+                .map(synthetic_claim)
+                .collect::<Vec<_>>();
+            writer.write(&claims).unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    fn num_row_groups(path: &str) -> usize {
+        let reader = SerializedFileReader::new(fs::File::open(path).unwrap()).unwrap();
+        reader.metadata().num_row_groups()
+    }
+
+    fn expected_stat(rows: u64) -> (U256, U256) {
+        let sum = (0..rows as u128).sum::<u128>() * 1_000_000_000;
+        (
+            U256::from(sum),
+            U256::from((rows as u128 - 1) * 1_000_000_000),
+        )
+    }
+
+    #[test]
+    fn test_row_groups_flushed_by_rows() {
+        let path = std::env::temp_dir().join("pq_test_row_groups_by_rows.parquet");
+        let path = path.to_str().unwrap();
+        write_synthetic(path, 25_000, 3_000, 10_000, DEFAULT_MAX_ROW_GROUP_BYTES);
+        assert_eq!(num_row_groups(path), 3);
+
+        let (sum, max) = expected_stat(25_000);
+        let stat = read_reward_file_stat(path).unwrap();
+        assert_eq!(stat.count, 25_000);
+        assert_eq!(stat.sum, sum);
+        assert_eq!(stat.claimed_max, max);
+        assert_eq!(stat.claimed_min, U256::ZERO);
+        assert_eq!(read_address_column(path, "earner").unwrap().len(), 25_000);
+        assert_eq!(
+            read_reward_file(path).unwrap()[24_999],
+            synthetic_claim(24_999)
+        );
+    }
+
+    #[test]
+    fn test_row_groups_flushed_by_bytes() {
+        let path = std::env::temp_dir().join("pq_test_row_groups_by_bytes.parquet");
+        let path = path.to_str().unwrap();
+        write_synthetic(path, 20_000, 1_000, DEFAULT_MAX_ROW_GROUP_ROWS, 64 * 1024);
+        assert!(num_row_groups(path) > 1);
+        assert_eq!(read_reward_file_stat(path).unwrap().count, 20_000);
+        assert_eq!(read_reward_file_stat_arrow(path).unwrap().count, 20_000);
+    }
+
+    #[test]
+    fn test_parallel_stat_matches_sequential() {
+        let path = std::env::temp_dir().join("pq_test_parallel_stat.parquet");
+        let path = path.to_str().unwrap();
+        write_synthetic(path, 25_000, 3_000, 2_000, DEFAULT_MAX_ROW_GROUP_BYTES);
+        assert_eq!(num_row_groups(path), 13);
+
+        let stat = read_reward_file_stat_arrow(path).unwrap();
+        for threads in [1, 2, 4, 32] {
+            assert_eq!(read_reward_file_stat_parallel(path, threads).unwrap(), stat);
+        }
+        assert!(read_reward_file_stat_parallel(path, 0).is_err());
+    }
+
+    #[test]
+    #[ignore] // writes several million rows, run with --ignored
+    fn test_multi_million_rows() {
+        let path = std::env::temp_dir().join("pq_test_multi_million_rows.parquet");
+        let path = path.to_str().unwrap();
+        let rows = 4_000_000;
+        write_synthetic(path, rows, 100_000, 500_000, DEFAULT_MAX_ROW_GROUP_BYTES);
+        assert_eq!(num_row_groups(path), 8);
+
+        let (sum, max) = expected_stat(rows);
+        let stat = read_reward_file_stat(path).unwrap();
+        assert_eq!(stat.count, rows);
+        assert_eq!(stat.sum, sum);
+        assert_eq!(stat.claimed_max, max);
+        let arrow_stat = read_reward_file_stat_arrow(path).unwrap();
+        assert_eq!(arrow_stat.count, rows);
+        assert_eq!(arrow_stat.sum, sum);
+        assert_eq!(RewardsClaimedIter::open(path).unwrap().count() as u64, rows);
+    }
+}
@@ -0,0 +1,327 @@
+use std::{fs, ops::Range, path::Path};
+
+use alloy::primitives::{Address, U256};
+use parquet::{
+    arrow::arrow_reader::{ArrowReaderOptions, ParquetRecordBatchReaderBuilder, RowSelection},
+    data_type::FixedLenByteArray,
+    file::{
+        metadata::ParquetMetaData,
+        page_index::index::Index,
+        properties::ReaderProperties,
+        reader::{FileReader, SerializedFileReader},
+        serialized_reader::ReadOptionsBuilder,
+    },
+};
+
+use crate::parquet::{column_index, parse_address};
+use crate::record_batch;
+
+/// A filter on the claims table.
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    Earner(Address),
+    Token(Address),
+    /// Claimed amounts in `[min, max]`.
+    ClaimedAmount {
+        min: u128,
+        max: u128,
+    },
+}
+
+impl Predicate {
+    fn column(&self) -> &str {
+        match self {
+            Predicate::Earner(_) => "earner",
+            Predicate::Token(_) => "token",
+            Predicate::ClaimedAmount { .. } => "claimedAmount",
+        }
+    }
+
+    /// Whether a column chunk or page with these min and max values may hold a match.
+    fn may_match(&self, min: &[u8], max: &[u8]) -> bool {
+        match self {
+            Predicate::Earner(address) | Predicate::Token(address) => {
+                min <= address.as_slice() && address.as_slice() <= max
+            }
+            Predicate::ClaimedAmount { min: from, max: to } => {
+                U256::from_be_slice(min) <= U256::from(*to)
+                    && U256::from(*from) <= U256::from_be_slice(max)
+            }
+        }
+    }
+
+    /// The value to probe the bloom filter with, for equality predicates.
+    fn bloom_filter_value(&self) -> Option<FixedLenByteArray> {
+        match self {
+            Predicate::Earner(address) | Predicate::Token(address) => {
+                Some(FixedLenByteArray::from(address.to_vec()))
+            }
+            Predicate::ClaimedAmount { .. } => None,
+        }
+    }
+
+    fn matches(&self, claim: &eigen_types::RewardsClaimed) -> bool {
+        match self {
+            Predicate::Earner(address) => claim.earner == address.to_checksum(None),
+            Predicate::Token(address) => claim.token == address.to_checksum(None),
+            Predicate::ClaimedAmount { min, max } => {
+                *min <= claim.claimed_amount && claim.claimed_amount <= *max
+            }
+        }
+    }
+}
+
+/// How much of the file a query could skip without decoding it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QueryStat {
+    pub row_groups: usize,
+    /// Row groups whose column statistics exclude the predicate.
+    pub row_groups_skipped_by_statistics: usize,
+    /// Row groups whose bloom filter excludes the value.
+    pub row_groups_skipped_by_bloom_filter: usize,
+    /// Pages of the predicate column in the row groups that were read.
+    pub pages: usize,
+    /// Pages whose page index statistics exclude the predicate.
+    pub pages_skipped: usize,
+}
+
+#[derive(Debug)]
+pub struct QueryResult {
+    pub claims: Vec<eigen_types::RewardsClaimed>,
+    pub stat: QueryStat,
+}
+
+/// Claims by `earner`.
+pub fn claims_for_earner(path: &str, earner: &str) -> eyre::Result<QueryResult> {
+    query(path, &Predicate::Earner(parse_address(earner)?))
+}
+
+/// Claims of `token`.
+pub fn claims_for_token(path: &str, token: &str) -> eyre::Result<QueryResult> {
+    query(path, &Predicate::Token(parse_address(token)?))
+}
+
+/// Claims with an amount in `[min, max]`.
+pub fn claims_in_amount_range(path: &str, min: u128, max: u128) -> eyre::Result<QueryResult> {
+    query(path, &Predicate::ClaimedAmount { min, max })
+}
+
+/// Read the claims matching `predicate`, skipping row groups using the column statistics and
+/// bloom filters, then pages using the page index.
+pub fn query(path: &str, predicate: &Predicate) -> eyre::Result<QueryResult> {
+    let mut stat = QueryStat::default();
+    let row_groups = prune_row_groups(path, predicate, &mut stat)?;
+    if row_groups.is_empty() {
+        return Ok(QueryResult {
+            claims: Vec::new(),
+            stat,
+        });
+    }
+
+    let file = fs::File::open(Path::new(path))?;
+    let options = ArrowReaderOptions::new().with_page_index(true);
+    let builder = ParquetRecordBatchReaderBuilder::try_new_with_options(file, options)?;
+    let selection = select_pages(builder.metadata(), &row_groups, predicate, &mut stat)?;
+    let reader = builder
+        .with_row_groups(row_groups)
+        .with_row_selection(selection)
+        .build()?;
+    let mut claims = Vec::new();
+    for batch in reader {
+        claims.extend(
+            record_batch::from_record_batch(&batch?)?
+                .into_iter()
+                .filter(|claim| predicate.matches(claim)),
+        );
+    }
+    Ok(QueryResult { claims, stat })
+}
+
+/// The row groups that may hold a match.
+fn prune_row_groups(
+    path: &str,
+    predicate: &Predicate,
+    stat: &mut QueryStat,
+) -> eyre::Result<Vec<usize>> {
+    let file = fs::File::open(Path::new(path))?;
+    let options = ReadOptionsBuilder::new()
+        .with_reader_properties(
+            ReaderProperties::builder()
+                .set_read_bloom_filter(true)
+                .build(),
+        )
+        .build();
+    let reader = SerializedFileReader::new_with_options(file, options)?;
+    let metadata = reader.metadata();
+    let column = column_index(metadata, predicate.column())?;
+    let bloom_filter_value = predicate.bloom_filter_value();
+    stat.row_groups = metadata.num_row_groups();
+    let mut row_groups = Vec::new();
+    for i in 0..metadata.num_row_groups() {
+        let statistics = metadata.row_group(i).column(column).statistics();
+        let min_max = statistics.and_then(|s| s.min_bytes_opt().zip(s.max_bytes_opt()));
+        if let Some((min, max)) = min_max {
+            if !predicate.may_match(min, max) {
+                stat.row_groups_skipped_by_statistics += 1;
+                continue;
+            }
+        }
+        if let Some(value) = &bloom_filter_value {
+            let row_group_reader = reader.get_row_group(i)?;
+            if let Some(bloom_filter) = row_group_reader.get_column_bloom_filter(column) {
+                if !bloom_filter.check(value) {
+                    stat.row_groups_skipped_by_bloom_filter += 1;
+                    continue;
+                }
+            }
+        }
+        row_groups.push(i);
+    }
+    Ok(row_groups)
+}
+
+/// Select the rows of the pages in `row_groups` that may hold a match.  Row groups without a
+/// page index are read whole.
+fn select_pages(
+    metadata: &ParquetMetaData,
+    row_groups: &[usize],
+    predicate: &Predicate,
+    stat: &mut QueryStat,
+) -> eyre::Result<RowSelection> {
+    let column = column_index(metadata, predicate.column())?;
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    for &i in row_groups {
+        let num_rows = metadata.row_group(i).num_rows() as usize;
+        let page_index = metadata.column_index().zip(metadata.offset_index());
+        let pages =
+            page_index.and_then(
+                |(column_index, offset_index)| match &column_index[i][column] {
+                    Index::FIXED_LEN_BYTE_ARRAY(index) => {
+                        Some((&index.indexes, &offset_index[i][column].page_locations))
+                    }
+                    _ => None,
+                },
+            );
+        let Some((pages, locations)) = pages else {
+            ranges.push(offset..offset + num_rows);
+            offset += num_rows;
+            continue;
+        };
+        stat.pages += pages.len();
+        for (page, page_index) in pages.iter().enumerate() {
+            let first_row = locations[page].first_row_index as usize;
+            let end_row = locations
+                .get(page + 1)
+                .map_or(num_rows, |next| next.first_row_index as usize);
+            let skip = match (&page_index.min, &page_index.max) {
+                (Some(min), Some(max)) => !predicate.may_match(min.data(), max.data()),
+                _ => false,
+            };
+            if skip {
+                stat.pages_skipped += 1;
+            } else {
+                ranges.push(offset + first_row..offset + end_row);
+            }
+        }
+        offset += num_rows;
+    }
+    Ok(RowSelection::from_consecutive_ranges(
+        ranges.into_iter(),
+        offset,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parquet::ClaimsWriter;
+
+    fn address(i: u64) -> Address {
+        Address::left_padding_from(&i.to_be_bytes())
+    }
+
+    /// Claims sorted by token then earner, 1,000 per token and 100 per earner.  Earners are even.
+    fn write_sorted_claims(path: &str) -> Vec<eigen_types::RewardsClaimed> {
+        let claims = (0..4_000u64)
+            .map(|i| eigen_types::RewardsClaimed {
+                root: [(i % 256) as u8; 32],
+                earner: address(1_000 + 2 * (i / 100)).to_checksum(None),
+                claimer: address(1).to_checksum(None),
+                recipient: address(1).to_checksum(None),
+                token: address(100 + i / 1_000).to_checksum(None),
+                claimed_amount: i as u128,
+                block_number: i,
+                transaction_hash: [0; 32],
+                log_index: 0,
+                cost: None,
+            })
+            .collect::<Vec<_>>();
+        let config = Config {
+            max_row_group_rows: 1_000,
+            data_page_row_limit: 100,
+            ..Default::default()
+        };
+        let mut writer = ClaimsWriter::create(path, &config).unwrap();
+        writer.write(&claims).unwrap();
+        writer.close().unwrap();
+        claims
+    }
+
+    #[test]
+    fn test_claims_for_token_skips_row_groups() {
+        let path = std::env::temp_dir().join("pq_test_claims_for_token.parquet");
+        let path = path.to_str().unwrap();
+        let claims = write_sorted_claims(path);
+
+        let token = address(102).to_checksum(None);
+        let result = claims_for_token(path, &token).unwrap();
+        assert_eq!(result.claims, claims[2_000..3_000]);
+        assert_eq!(result.stat.row_groups, 4);
+        assert_eq!(result.stat.row_groups_skipped_by_statistics, 3);
+    }
+
+    #[test]
+    fn test_claims_for_earner_skips_pages() {
+        let path = std::env::temp_dir().join("pq_test_claims_for_earner.parquet");
+        let path = path.to_str().unwrap();
+        let claims = write_sorted_claims(path);
+
+        let earner = address(1_030).to_checksum(None);
+        let result = claims_for_earner(path, &earner).unwrap();
+        assert_eq!(result.claims, claims[1_500..1_600]);
+        assert_eq!(result.stat.row_groups_skipped_by_statistics, 3);
+        assert!(result.stat.pages > 1);
+        assert!(result.stat.pages_skipped > 0);
+    }
+
+    #[test]
+    fn test_bloom_filter_skips_absent_earner() {
+        let path = std::env::temp_dir().join("pq_test_bloom_filter.parquet");
+        let path = path.to_str().unwrap();
+        write_sorted_claims(path);
+
+        // within the statistics of the first row group but never written
+        let result = query(path, &Predicate::Earner(address(1_001))).unwrap();
+        assert!(result.claims.is_empty());
+        assert_eq!(result.stat.row_groups_skipped_by_statistics, 3);
+        assert_eq!(result.stat.row_groups_skipped_by_bloom_filter, 1);
+    }
+
+    #[test]
+    fn test_claims_in_amount_range() {
+        let path = std::env::temp_dir().join("pq_test_claims_in_amount_range.parquet");
+        let path = path.to_str().unwrap();
+        let claims = write_sorted_claims(path);
+
+        let result = claims_in_amount_range(path, 1_950, 2_049).unwrap();
+        assert_eq!(result.claims, claims[1_950..2_050]);
+        assert_eq!(result.stat.row_groups_skipped_by_statistics, 2);
+        assert!(result.stat.pages_skipped > 0);
+
+        let result = claims_in_amount_range(path, 5_000, 6_000).unwrap();
+        assert!(result.claims.is_empty());
+        assert_eq!(result.stat.row_groups_skipped_by_statistics, 4);
+    }
+}
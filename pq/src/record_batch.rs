@@ -0,0 +1,337 @@
+use std::sync::{Arc, OnceLock};
+
+use alloy::primitives::U256;
+use arrow::{
+    array::{Array, ArrayRef, Decimal256Array, FixedSizeBinaryArray, RecordBatch, UInt64Array},
+    compute,
+    datatypes::{i256, DataType, Field, Schema, SchemaRef},
+};
+
+use crate::parquet::{address_from_bytes, parse_address, ClaimedAmountStat};
+
+const ROOT_LEN: i32 = 32;
+pub(crate) const ADDRESS_LEN: i32 = 20;
+const HASH_LEN: i32 = 32;
+const AMOUNT_PRECISION: u8 = 76;
+
+/// The Arrow schema of the claims table.  Addresses are 20 byte binary, `claimedAmount` is a
+/// `Decimal256(76, 0)` in base units.  The cost columns, `gasUsed`, `effectiveGasPrice` and
+/// `feePaid` in wei, are null for claims without a cost and absent from files written before
+/// schema version 2.
+pub fn claims_schema() -> SchemaRef {
+    static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+    SCHEMA
+        .get_or_init(|| {
+            Arc::new(Schema::new(vec![
+                Field::new("root", DataType::FixedSizeBinary(ROOT_LEN), false),
+                Field::new("earner", DataType::FixedSizeBinary(ADDRESS_LEN), false),
+                Field::new("claimer", DataType::FixedSizeBinary(ADDRESS_LEN), false),
+                Field::new("recipient", DataType::FixedSizeBinary(ADDRESS_LEN), false),
+                Field::new("token", DataType::FixedSizeBinary(ADDRESS_LEN), false),
+                Field::new(
+                    "claimedAmount",
+                    DataType::Decimal256(AMOUNT_PRECISION, 0),
+                    false,
+                ),
+                Field::new("blockNumber", DataType::UInt64, false),
+                Field::new(
+                    "transactionHash",
+                    DataType::FixedSizeBinary(HASH_LEN),
+                    false,
+                ),
+                Field::new("logIndex", DataType::UInt64, false),
+                Field::new("gasUsed", DataType::UInt64, true),
+                Field::new(
+                    "effectiveGasPrice",
+                    DataType::Decimal256(AMOUNT_PRECISION, 0),
+                    true,
+                ),
+                Field::new("feePaid", DataType::Decimal256(AMOUNT_PRECISION, 0), true),
+            ]))
+        })
+        .clone()
+}
+
+fn binary_column<'a>(values: impl Iterator<Item = &'a [u8]>, size: i32) -> eyre::Result<ArrayRef> {
+    Ok(Arc::new(
+        FixedSizeBinaryArray::try_from_sparse_iter_with_size(values.map(Some), size)?,
+    ))
+}
+
+fn address_column(
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    address: impl Fn(&eigen_types::RewardsClaimed) -> &str,
+) -> eyre::Result<ArrayRef> {
+    let addresses = rewards_claimed
+        .iter()
+        .map(|r| parse_address(address(r)))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    binary_column(addresses.iter().map(|a| a.as_slice()), ADDRESS_LEN)
+}
+
+fn cost_amount_column(
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    amount: impl Fn(&eigen_types::ClaimCost) -> u128,
+) -> eyre::Result<ArrayRef> {
+    Ok(Arc::new(
+        Decimal256Array::from_iter(rewards_claimed.iter().map(|r| {
+            r.cost
+                .as_ref()
+                .map(|cost| i256::from_parts(amount(cost), 0))
+        }))
+        .with_precision_and_scale(AMOUNT_PRECISION, 0)?,
+    ))
+}
+
+/// Convert claims into a record batch with [`claims_schema`], validating every address.
+pub fn to_record_batch(
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> eyre::Result<RecordBatch> {
+    let claimed_amounts = Decimal256Array::from_iter_values(
+        rewards_claimed
+            .iter()
+            .map(|r| i256::from_parts(r.claimed_amount, 0)),
+    )
+    .with_precision_and_scale(AMOUNT_PRECISION, 0)?;
+    let columns: Vec<ArrayRef> = vec![
+        binary_column(rewards_claimed.iter().map(|r| r.root.as_slice()), ROOT_LEN)?,
+        address_column(rewards_claimed, |r| &r.earner)?,
+        address_column(rewards_claimed, |r| &r.claimer)?,
+        address_column(rewards_claimed, |r| &r.recipient)?,
+        address_column(rewards_claimed, |r| &r.token)?,
+        Arc::new(claimed_amounts),
+        Arc::new(UInt64Array::from_iter_values(
+            rewards_claimed.iter().map(|r| r.block_number),
+        )),
+        binary_column(
+            rewards_claimed
+                .iter()
+                .map(|r| r.transaction_hash.as_slice()),
+            HASH_LEN,
+        )?,
+        Arc::new(UInt64Array::from_iter_values(
+            rewards_claimed.iter().map(|r| r.log_index),
+        )),
+        Arc::new(UInt64Array::from_iter(
+            rewards_claimed
+                .iter()
+                .map(|r| r.cost.as_ref().map(|cost| cost.gas_used)),
+        )),
+        cost_amount_column(rewards_claimed, |cost| cost.effective_gas_price)?,
+        cost_amount_column(rewards_claimed, |cost| cost.fee_paid)?,
+    ];
+    Ok(RecordBatch::try_new(claims_schema(), columns)?)
+}
+
+fn column<'a, T: Array + 'static>(batch: &'a RecordBatch, name: &str) -> eyre::Result<&'a T> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| eyre::eyre!("No column named {}", name))?
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| eyre::eyre!("Unexpected type for column {}", name))
+}
+
+/// A column that files written before schema version 2 lack.
+fn optional_column<'a, T: Array + 'static>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> eyre::Result<Option<&'a T>> {
+    if batch.column_by_name(name).is_none() {
+        return Ok(None);
+    }
+    column(batch, name).map(Some)
+}
+
+fn to_u128(value: i256, name: &str, row: usize) -> eyre::Result<u128> {
+    let (value, high) = value.to_parts();
+    if high != 0 {
+        return Err(eyre::eyre!("{} out of range at row {}", name, row));
+    }
+    Ok(value)
+}
+
+/// The cost at row `i`, if the claim has one.
+fn claim_cost(
+    gas_used: Option<&UInt64Array>,
+    effective_gas_prices: Option<&Decimal256Array>,
+    fees_paid: Option<&Decimal256Array>,
+    i: usize,
+) -> eyre::Result<Option<eigen_types::ClaimCost>> {
+    let (Some(gas_used), Some(effective_gas_prices), Some(fees_paid)) =
+        (gas_used, effective_gas_prices, fees_paid)
+    else {
+        return Ok(None);
+    };
+    if gas_used.is_null(i) || effective_gas_prices.is_null(i) || fees_paid.is_null(i) {
+        return Ok(None);
+    }
+    Ok(Some(eigen_types::ClaimCost {
+        gas_used: gas_used.value(i),
+        effective_gas_price: to_u128(effective_gas_prices.value(i), "Effective gas price", i)?,
+        fee_paid: to_u128(fees_paid.value(i), "Fee paid", i)?,
+    }))
+}
+
+/// Convert a record batch with [`claims_schema`] back into claims.  Batches without the cost
+/// columns give claims without a cost.
+pub fn from_record_batch(batch: &RecordBatch) -> eyre::Result<Vec<eigen_types::RewardsClaimed>> {
+    let roots = column::<FixedSizeBinaryArray>(batch, "root")?;
+    let earners = column::<FixedSizeBinaryArray>(batch, "earner")?;
+    let claimers = column::<FixedSizeBinaryArray>(batch, "claimer")?;
+    let recipients = column::<FixedSizeBinaryArray>(batch, "recipient")?;
+    let tokens = column::<FixedSizeBinaryArray>(batch, "token")?;
+    let claimed_amounts = column::<Decimal256Array>(batch, "claimedAmount")?;
+    let block_numbers = column::<UInt64Array>(batch, "blockNumber")?;
+    let transaction_hashes = column::<FixedSizeBinaryArray>(batch, "transactionHash")?;
+    let log_indexes = column::<UInt64Array>(batch, "logIndex")?;
+    let gas_used = optional_column::<UInt64Array>(batch, "gasUsed")?;
+    let effective_gas_prices = optional_column::<Decimal256Array>(batch, "effectiveGasPrice")?;
+    let fees_paid = optional_column::<Decimal256Array>(batch, "feePaid")?;
+    (0..batch.num_rows())
+        .map(|i| {
+            Ok(eigen_types::RewardsClaimed {
+                root: roots.value(i).try_into()?,
+                earner: address_from_bytes(earners.value(i))?,
+                claimer: address_from_bytes(claimers.value(i))?,
+                recipient: address_from_bytes(recipients.value(i))?,
+                token: address_from_bytes(tokens.value(i))?,
+                claimed_amount: to_u128(claimed_amounts.value(i), "Claimed amount", i)?,
+                block_number: block_numbers.value(i),
+                transaction_hash: transaction_hashes.value(i).try_into()?,
+                log_index: log_indexes.value(i),
+                cost: claim_cost(gas_used, effective_gas_prices, fees_paid, i)?,
+            })
+        })
+        .collect()
+}
+
+/// The `(claimedAmount, feePaid)` of each claim with a fee in a batch projected to those two
+/// columns, for [`eigen_types::ClaimCostStat::from_fees`].
+pub fn claim_fees(batch: &RecordBatch) -> eyre::Result<Vec<(u128, u128)>> {
+    let claimed_amounts = column::<Decimal256Array>(batch, "claimedAmount")?;
+    let fees_paid = column::<Decimal256Array>(batch, "feePaid")?;
+    (0..batch.num_rows())
+        .filter(|&i| fees_paid.is_valid(i))
+        .map(|i| {
+            Ok((
+                to_u128(claimed_amounts.value(i), "Claimed amount", i)?,
+                to_u128(fees_paid.value(i), "Fee paid", i)?,
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn to_u256(value: i256) -> U256 {
+    U256::from_be_bytes(value.to_be_bytes())
+}
+
+/// Claimed amount statistics for one batch, computed with the Arrow aggregate kernels.
+pub fn claimed_amount_stat(batch: &RecordBatch) -> eyre::Result<ClaimedAmountStat> {
+    let claimed_amounts = column::<Decimal256Array>(batch, "claimedAmount")?;
+    let mut stat = ClaimedAmountStat::default();
+    let Some(sum) = compute::sum_checked(claimed_amounts)? else {
+        return Ok(stat);
+    };
+    stat.sum = to_u256(sum);
+    stat.count = (claimed_amounts.len() - claimed_amounts.null_count()) as u64;
+    stat.claimed_mean = stat.sum / U256::from(stat.count);
+    stat.claimed_max = compute::max(claimed_amounts).map_or(U256::ZERO, to_u256);
+    stat.claimed_min = compute::min(claimed_amounts).map_or(U256::MAX, to_u256);
+    Ok(stat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(claimed_amount: u128, log_index: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            claimer: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            recipient: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            token: "0x3B78576F7D6837500bA3De27A60c7f594934027E".to_string(),
+            claimed_amount,
+            block_number: 1671211,
+            transaction_hash: [2; 32],
+            log_index,
+            cost: None,
+        }
+    }
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let claims = vec![claim(1, 0), claim(u128::MAX, 1)];
+        let batch = to_record_batch(&claims).unwrap();
+        assert_eq!(batch.schema(), claims_schema());
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(from_record_batch(&batch).unwrap(), claims);
+
+        let empty = to_record_batch(&[]).unwrap();
+        assert_eq!(empty.num_rows(), 0);
+    }
+
+    fn with_cost(
+        claim: eigen_types::RewardsClaimed,
+        fee_paid: u128,
+    ) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            cost: Some(eigen_types::ClaimCost {
+                gas_used: 120_000,
+                effective_gas_price: u128::MAX,
+                fee_paid,
+            }),
+            ..claim
+        }
+    }
+
+    #[test]
+    fn test_claim_cost_round_trip() {
+        let claims = vec![
+            with_cost(claim(10, 0), 4),
+            claim(1, 1),
+            with_cost(claim(0, 2), 3),
+        ];
+        let batch = to_record_batch(&claims).unwrap();
+        assert_eq!(batch.column_by_name("feePaid").unwrap().null_count(), 1);
+        assert_eq!(from_record_batch(&batch).unwrap(), claims);
+
+        let fees = batch
+            .project(&[
+                batch.schema().index_of("claimedAmount").unwrap(),
+                batch.schema().index_of("feePaid").unwrap(),
+            ])
+            .unwrap();
+        assert_eq!(claim_fees(&fees).unwrap(), vec![(10, 4), (0, 3)]);
+    }
+
+    #[test]
+    fn test_from_record_batch_without_cost_columns() {
+        let claims = vec![with_cost(claim(10, 0), 4)];
+        let batch = to_record_batch(&claims).unwrap();
+        // the layout of schema version 1
+        let batch = batch.project(&(0..9).collect::<Vec<_>>()).unwrap();
+        let read = from_record_batch(&batch).unwrap();
+        assert_eq!(read, vec![claim(10, 0)]);
+    }
+
+    #[test]
+    fn test_claimed_amount_stat() {
+        let claims = vec![
+            claim(1_000_000_000_000_000_000, 0),
+            claim(2_500_000_000_000_000_001, 1),
+            claim(u128::MAX, 2),
+        ];
+        let stat = claimed_amount_stat(&to_record_batch(&claims).unwrap()).unwrap();
+        let sum = U256::from(3_500_000_000_000_000_001u128) + U256::from(u128::MAX);
+        assert_eq!(stat.count, 3);
+        assert_eq!(stat.sum, sum);
+        assert_eq!(stat.claimed_mean, sum / U256::from(3));
+        assert_eq!(stat.claimed_max, U256::from(u128::MAX));
+        assert_eq!(stat.claimed_min, U256::from(1_000_000_000_000_000_000u128));
+
+        let stat = claimed_amount_stat(&to_record_batch(&[]).unwrap()).unwrap();
+        assert_eq!(stat.count, 0);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{parquet, submission};
+
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+/// Writes indexer batches into a directory of `part-<block>` Parquet parts.
+///
+/// A part is written to a temporary directory and renamed into place before the checkpoint
+/// file is replaced.  Parts past the checkpoint are removed on open, so the committed data
+/// always matches the checkpoint.
+pub struct ParquetSink {
+    dir: PathBuf,
+}
+
+impl ParquetSink {
+    pub fn open(dir: &str) -> eyre::Result<Self> {
+        let sink = ParquetSink {
+            dir: PathBuf::from(dir),
+        };
+        fs::create_dir_all(&sink.dir)?;
+        let checkpoint = sink.checkpoint()?;
+        for entry in fs::read_dir(&sink.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let uncommitted = match part_block(name) {
+                Some(block_number) => match checkpoint {
+                    Some(c) => block_number > c,
+                    None => true,
+                },
+                None => name.ends_with(".tmp"),
+            };
+            if uncommitted && path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            }
+        }
+        Ok(sink)
+    }
+
+    /// The last block committed to this sink, if any.
+    pub fn checkpoint(&self) -> eyre::Result<Option<u64>> {
+        match fs::read_to_string(self.dir.join(CHECKPOINT_FILE)) {
+            Ok(checkpoint) => Ok(Some(checkpoint.trim().parse()?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write a batch of indexed events as a new part and advance the checkpoint to `block_number`.
+    pub fn write_batch(
+        &mut self,
+        rewards_claimed: &[eigen_types::RewardsClaimed],
+        rewards_submissions: &[eigen_types::RewardsSubmission],
+        block_number: u64,
+    ) -> eyre::Result<()> {
+        if !rewards_claimed.is_empty() || !rewards_submissions.is_empty() {
+            let part = format!("part-{:012}", block_number);
+            let tmp = self.dir.join(format!("{}.tmp", part));
+            fs::create_dir_all(&tmp)?;
+            parquet::write_parquet_file(
+                &path_str(&tmp, "rewards_claimed.parquet"),
+                rewards_claimed,
+            )?;
+            submission::write_submissions_parquet_file(
+                &path_str(&tmp, "rewards_submission.parquet"),
+                &path_str(&tmp, "rewards_submission_strategy.parquet"),
+                rewards_submissions,
+            )?;
+            fs::rename(&tmp, self.dir.join(part))?;
+        }
+        let tmp = self.dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        fs::write(&tmp, block_number.to_string())?;
+        fs::rename(&tmp, self.dir.join(CHECKPOINT_FILE))?;
+        Ok(())
+    }
+}
+
+fn part_block(name: &str) -> Option<u64> {
+    name.strip_prefix("part-")?.parse().ok()
+}
+
+fn path_str(dir: &Path, file_name: &str) -> String {
+    dir.join(file_name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncommitted_parts_removed() {
+        let dir = std::env::temp_dir().join("pq_test_sink");
+        let _ = fs::remove_dir_all(&dir);
+        let dir = dir.to_str().unwrap();
+
+        let mut sink = ParquetSink::open(dir).unwrap();
+        assert_eq!(sink.checkpoint().unwrap(), None);
+        sink.write_batch(&[], &[], 100).unwrap();
+        assert_eq!(sink.checkpoint().unwrap(), Some(100));
+
+        // a part renamed into place without its checkpoint
+        fs::create_dir_all(Path::new(dir).join("part-000000000200")).unwrap();
+        fs::create_dir_all(Path::new(dir).join("part-000000000300.tmp")).unwrap();
+        let sink = ParquetSink::open(dir).unwrap();
+        assert_eq!(sink.checkpoint().unwrap(), Some(100));
+        assert!(!Path::new(dir).join("part-000000000200").exists());
+        assert!(!Path::new(dir).join("part-000000000300.tmp").exists());
+    }
+}
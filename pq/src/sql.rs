@@ -0,0 +1,427 @@
+use std::{any::Any, str::FromStr, sync::Arc};
+
+use alloy::primitives::{utils::format_units, Address};
+use arrow::{
+    array::{
+        Array, ArrayRef, Decimal256Array, FixedSizeBinaryArray, Int64Array, RecordBatch,
+        StringArray,
+    },
+    datatypes::{DataType, Field, Schema},
+};
+use datafusion::{
+    common::{DataFusionError, Result as DataFusionResult},
+    logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility},
+    prelude::{ParquetReadOptions, SessionContext},
+};
+
+use crate::parquet::{address_from_bytes, parse_address};
+use crate::record_batch::{to_u256, ADDRESS_LEN};
+
+/// The table name the claims are registered under.
+pub const CLAIMS_TABLE: &str = "claims";
+
+/// How query results are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(eyre::eyre!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+/// A session with the claims file or directory at `path` registered as [`CLAIMS_TABLE`] and
+/// the claims UDFs:
+///
+/// - `address(earner)` formats a 20 byte address column as a checksummed address.
+/// - `parse_address('0x...')` parses an address for comparison with an address column.
+/// - `format_units(claimedAmount, 18)` formats a 256-bit amount with the token's decimals.
+pub async fn session(path: &str) -> eyre::Result<SessionContext> {
+    let ctx = SessionContext::new();
+    ctx.register_parquet(CLAIMS_TABLE, path, ParquetReadOptions::default())
+        .await?;
+    ctx.register_udf(ScalarUDF::new_from_impl(AddressUdf::new()));
+    ctx.register_udf(ScalarUDF::new_from_impl(ParseAddressUdf::new()));
+    ctx.register_udf(ScalarUDF::new_from_impl(FormatUnitsUdf::new()));
+    Ok(ctx)
+}
+
+/// Run `sql` against the claims at `path`.
+pub async fn query(path: &str, sql: &str) -> eyre::Result<Vec<RecordBatch>> {
+    let ctx = session(path).await?;
+    Ok(ctx.sql(sql).await?.collect().await?)
+}
+
+fn execution_error(message: String) -> DataFusionError {
+    DataFusionError::Execution(message)
+}
+
+fn downcast<'a, T: Array + 'static>(array: &'a ArrayRef, udf: &str) -> DataFusionResult<&'a T> {
+    array
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| execution_error(format!("Unexpected argument type for {}", udf)))
+}
+
+#[derive(Debug)]
+struct AddressUdf {
+    signature: Signature,
+}
+
+impl AddressUdf {
+    fn new() -> Self {
+        AddressUdf {
+            signature: Signature::exact(
+                vec![DataType::FixedSizeBinary(ADDRESS_LEN)],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl ScalarUDFImpl for AddressUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "address"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DataFusionResult<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> DataFusionResult<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let addresses = downcast::<FixedSizeBinaryArray>(&arrays[0], self.name())?;
+        let formatted = addresses
+            .iter()
+            .map(|address| {
+                address
+                    .map(address_from_bytes)
+                    .transpose()
+                    .map_err(|e| execution_error(e.to_string()))
+            })
+            .collect::<DataFusionResult<StringArray>>()?;
+        Ok(ColumnarValue::Array(Arc::new(formatted)))
+    }
+}
+
+#[derive(Debug)]
+struct ParseAddressUdf {
+    signature: Signature,
+}
+
+impl ParseAddressUdf {
+    fn new() -> Self {
+        ParseAddressUdf {
+            signature: Signature::exact(vec![DataType::Utf8], Volatility::Immutable),
+        }
+    }
+}
+
+impl ScalarUDFImpl for ParseAddressUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "parse_address"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DataFusionResult<DataType> {
+        Ok(DataType::FixedSizeBinary(ADDRESS_LEN))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> DataFusionResult<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let addresses = downcast::<StringArray>(&arrays[0], self.name())?
+            .iter()
+            .map(|address| {
+                address
+                    .map(parse_address)
+                    .transpose()
+                    .map_err(|e| execution_error(e.to_string()))
+            })
+            .collect::<DataFusionResult<Vec<Option<Address>>>>()?;
+        let parsed = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+            addresses.iter().map(|a| a.as_ref().map(|a| a.as_slice())),
+            ADDRESS_LEN,
+        )?;
+        Ok(ColumnarValue::Array(Arc::new(parsed)))
+    }
+}
+
+#[derive(Debug)]
+struct FormatUnitsUdf {
+    signature: Signature,
+}
+
+impl FormatUnitsUdf {
+    fn new() -> Self {
+        FormatUnitsUdf {
+            signature: Signature::exact(
+                vec![DataType::Decimal256(76, 0), DataType::Int64],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl ScalarUDFImpl for FormatUnitsUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "format_units"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DataFusionResult<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> DataFusionResult<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let amounts = downcast::<Decimal256Array>(&arrays[0], self.name())?;
+        let decimals = downcast::<Int64Array>(&arrays[1], self.name())?;
+        let formatted = amounts
+            .iter()
+            .zip(decimals.iter())
+            .map(|(amount, decimals)| {
+                let (Some(amount), Some(decimals)) = (amount, decimals) else {
+                    return Ok(None);
+                };
+                if amount.is_negative() {
+                    return Err(execution_error(format!("Negative amount: {}", amount)));
+                }
+                let decimals = u8::try_from(decimals)
+                    .map_err(|_| execution_error(format!("Invalid decimals: {}", decimals)))?;
+                format_units(to_u256(amount), decimals)
+                    .map(Some)
+                    .map_err(|e| execution_error(e.to_string()))
+            })
+            .collect::<DataFusionResult<StringArray>>()?;
+        Ok(ColumnarValue::Array(Arc::new(formatted)))
+    }
+}
+
+/// Convert the columns that have no readable text form: 20 byte binary to checksummed
+/// addresses, other fixed size binary to `0x` hex and decimals to exact strings.
+fn displayable(batch: &RecordBatch) -> eyre::Result<RecordBatch> {
+    let mut fields = Vec::new();
+    let mut columns: Vec<ArrayRef> = Vec::new();
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let text: Option<StringArray> = match field.data_type() {
+            DataType::FixedSizeBinary(ADDRESS_LEN) => Some(
+                downcast::<FixedSizeBinaryArray>(column, field.name())?
+                    .iter()
+                    .map(|address| address.map(address_from_bytes).transpose())
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            DataType::FixedSizeBinary(_) => Some(
+                downcast::<FixedSizeBinaryArray>(column, field.name())?
+                    .iter()
+                    .map(|bytes| {
+                        bytes.map(|bytes| format!("0x{}", alloy::primitives::hex::encode(bytes)))
+                    })
+                    .collect(),
+            ),
+            DataType::Decimal256(_, _) => {
+                let decimals = downcast::<Decimal256Array>(column, field.name())?;
+                Some(
+                    (0..decimals.len())
+                        .map(|i| (!decimals.is_null(i)).then(|| decimals.value_as_string(i)))
+                        .collect(),
+                )
+            }
+            _ => None,
+        };
+        match text {
+            Some(text) => {
+                fields.push(Field::new(
+                    field.name(),
+                    DataType::Utf8,
+                    field.is_nullable(),
+                ));
+                columns.push(Arc::new(text));
+            }
+            None => {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+            }
+        }
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Render query results as an ASCII table, CSV with a header row, or a JSON array of objects.
+pub fn format_batches(batches: &[RecordBatch], format: OutputFormat) -> eyre::Result<String> {
+    let batches = batches
+        .iter()
+        .map(displayable)
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let output = match format {
+        OutputFormat::Table => arrow::util::pretty::pretty_format_batches(&batches)?
+            .to_string()
+            .into_bytes(),
+        OutputFormat::Csv => {
+            let mut writer = arrow::csv::WriterBuilder::new()
+                .with_header(true)
+                .build(Vec::new());
+            for batch in &batches {
+                writer.write(batch)?;
+            }
+            writer.into_inner()
+        }
+        OutputFormat::Json => {
+            let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+            writer.write_batches(&batches.iter().collect::<Vec<_>>())?;
+            writer.finish()?;
+            writer.into_inner()
+        }
+    };
+    Ok(String::from_utf8(output)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parquet::ClaimsWriter;
+
+    const EARNER: &str = "0xAcc1fb458a1317E886dB376Fc8141540537E68fE";
+    const TOKEN_A: &str = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+    const TOKEN_B: &str = "0x8aC7b6Cf2d7fa5cC0AC6B4D81f4E4A2ec43Ae7C0";
+
+    fn write_claims(name: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap().to_string();
+        let claims = [
+            (TOKEN_A, 1_000_000_000_000_000_000u128),
+            (TOKEN_A, 2_500_000_000_000_000_000),
+            (TOKEN_B, u128::MAX),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (token, claimed_amount))| eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: EARNER.to_string(),
+            claimer: EARNER.to_string(),
+            recipient: EARNER.to_string(),
+            token: token.to_string(),
+            claimed_amount: *claimed_amount,
+            block_number: i as u64,
+            transaction_hash: [2; 32],
+            log_index: i as u64,
+            cost: None,
+        })
+        .collect::<Vec<_>>();
+        let mut writer = ClaimsWriter::create(&path, &Config::default()).unwrap();
+        writer.write(&claims).unwrap();
+        writer.close().unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_group_by_token() {
+        let path = write_claims("pq_test_sql_group_by.parquet");
+        let batches = query(
+            &path,
+            "SELECT address(token) AS address, sum(\"claimedAmount\") AS total \
+             FROM claims GROUP BY token ORDER BY total",
+        )
+        .await
+        .unwrap();
+        let csv = format_batches(&batches, OutputFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "address,total\n{},3500000000000000000\n{},{}\n",
+                TOKEN_A,
+                TOKEN_B,
+                u128::MAX
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_address_and_format_units() {
+        let path = write_claims("pq_test_sql_udfs.parquet");
+        let batches = query(
+            &path,
+            &format!(
+                "SELECT format_units(\"claimedAmount\", 18) AS amount FROM claims \
+                 WHERE token = parse_address('{}') ORDER BY \"logIndex\"",
+                TOKEN_A.to_lowercase()
+            ),
+        )
+        .await
+        .unwrap();
+        let json = format_batches(&batches, OutputFormat::Json).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"amount":"1.000000000000000000"},{"amount":"2.500000000000000000"}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_output() {
+        let path = write_claims("pq_test_sql_table.parquet");
+        let batches = query(&path, "SELECT earner, \"blockNumber\" FROM claims LIMIT 1")
+            .await
+            .unwrap();
+        let table = format_batches(&batches, OutputFormat::Table).unwrap();
+        assert!(table.contains(EARNER));
+        assert!(table.contains("blockNumber"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_address_rejected() {
+        let path = write_claims("pq_test_sql_invalid_address.parquet");
+        let result = query(
+            &path,
+            "SELECT * FROM claims WHERE earner = parse_address('0x1234')",
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!(
+            "table".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Table
+        );
+        assert_eq!("csv".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,476 @@
+use std::{fs, path::Path, sync::Arc};
+
+use bytes::Bytes;
+use eigen_types::ClaimCostStat;
+use futures::TryStreamExt;
+use memmap2::Mmap;
+use object_store::{
+    aws::AmazonS3Builder, local::LocalFileSystem, path::Path as ObjectPath, ObjectStore, PutPayload,
+};
+use parquet::arrow::{
+    async_reader::ParquetObjectReader, ParquetRecordBatchStreamBuilder, ProjectionMask,
+};
+
+use crate::config::Config;
+use crate::metadata::Metadata;
+use crate::parquet::{column_index, read_chunk_stat, ClaimedAmountStat, ClaimsWriter};
+use crate::record_batch;
+
+/// Resolve a location into an object store and the path of the object in it.
+///
+/// `s3://<bucket>/<key>` uses S3, configured from the `AWS_*` environment variables; set
+/// `AWS_ENDPOINT` and `AWS_ALLOW_HTTP` for an S3 compatible service.  Anything else, with or
+/// without a `file://` prefix, is a local path.
+pub fn object_store(location: &str) -> eyre::Result<(Arc<dyn ObjectStore>, ObjectPath)> {
+    if let Some(bucket_key) = location.strip_prefix("s3://") {
+        let (bucket, key) = bucket_key.split_once('/').unwrap_or((bucket_key, ""));
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()?;
+        return Ok((Arc::new(store), ObjectPath::parse(key)?));
+    }
+    let path = location.strip_prefix("file://").unwrap_or(location);
+    let path = std::env::current_dir()?.join(path);
+    Ok((
+        Arc::new(LocalFileSystem::new()),
+        ObjectPath::from_absolute_path(path)?,
+    ))
+}
+
+/// Write the claims to `path` in `store`.  The file is built in memory and uploaded with a
+/// single put, so it is never visible partially written.
+pub async fn put_claims(
+    store: &dyn ObjectStore,
+    path: &ObjectPath,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    config: &Config,
+    metadata: Metadata,
+) -> eyre::Result<Metadata> {
+    let mut writer = ClaimsWriter::new(Vec::new(), config)?.with_metadata(metadata);
+    writer.write(rewards_claimed)?;
+    let (buffer, metadata) = writer.into_inner()?;
+    store.put(path, PutPayload::from(buffer)).await?;
+    Ok(metadata)
+}
+
+/// Claimed amount statistics of the claims file at `path`.  Only the footer and the
+/// `claimedAmount` column chunks are fetched, as range reads.
+pub async fn read_reward_stat(
+    store: Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+) -> eyre::Result<ClaimedAmountStat> {
+    let meta = store.head(path).await?;
+    let reader = ParquetObjectReader::new(store, meta);
+    let builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+    let claimed_amount_column = column_index(builder.metadata(), "claimedAmount")?;
+    let projection = ProjectionMask::leaves(builder.parquet_schema(), [claimed_amount_column]);
+    let mut stream = builder.with_projection(projection).build()?;
+    let mut claimed_stat = ClaimedAmountStat::default();
+    while let Some(batch) = stream.try_next().await? {
+        claimed_stat.merge(&record_batch::claimed_amount_stat(&batch)?);
+    }
+    Ok(claimed_stat)
+}
+
+/// Claimed amount statistics over every `.parquet` object under `prefix`, such as a
+/// partitioned dataset.
+pub async fn read_dataset_stat(
+    store: Arc<dyn ObjectStore>,
+    prefix: &ObjectPath,
+) -> eyre::Result<ClaimedAmountStat> {
+    let objects = store.list(Some(prefix)).try_collect::<Vec<_>>().await?;
+    let mut claimed_stat = ClaimedAmountStat::default();
+    for object in objects {
+        if object.location.extension() == Some("parquet") {
+            claimed_stat.merge(&read_reward_stat(store.clone(), &object.location).await?);
+        }
+    }
+    Ok(claimed_stat)
+}
+
+/// The `(claimedAmount, feePaid)` of each claim with a cost in the file at `path`, fetching only
+/// those two columns.  Files written before costs were recorded have none.
+async fn read_claim_fees(
+    store: Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+) -> eyre::Result<Vec<(u128, u128)>> {
+    let meta = store.head(path).await?;
+    let reader = ParquetObjectReader::new(store, meta);
+    let builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+    if builder.schema().column_with_name("feePaid").is_none() {
+        return Ok(Vec::new());
+    }
+    let columns = [
+        column_index(builder.metadata(), "claimedAmount")?,
+        column_index(builder.metadata(), "feePaid")?,
+    ];
+    let projection = ProjectionMask::leaves(builder.parquet_schema(), columns);
+    let mut stream = builder.with_projection(projection).build()?;
+    let mut fees = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        fees.extend(record_batch::claim_fees(&batch)?);
+    }
+    Ok(fees)
+}
+
+/// Fee statistics of the claims with a cost in the file at `path`.
+pub async fn read_claim_cost_stat(
+    store: Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+) -> eyre::Result<ClaimCostStat> {
+    Ok(ClaimCostStat::from_fees(
+        read_claim_fees(store, path).await?,
+    ))
+}
+
+/// Fee statistics over every `.parquet` object under `prefix`.
+pub async fn read_dataset_claim_cost_stat(
+    store: Arc<dyn ObjectStore>,
+    prefix: &ObjectPath,
+) -> eyre::Result<ClaimCostStat> {
+    let objects = store.list(Some(prefix)).try_collect::<Vec<_>>().await?;
+    let mut fees = Vec::new();
+    for object in objects {
+        if object.location.extension() == Some("parquet") {
+            fees.extend(read_claim_fees(store.clone(), &object.location).await?);
+        }
+    }
+    Ok(ClaimCostStat::from_fees(fees))
+}
+
+/// Read every claim in the file at `path`.
+pub async fn read_claims(
+    store: Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+) -> eyre::Result<Vec<eigen_types::RewardsClaimed>> {
+    let meta = store.head(path).await?;
+    let reader = ParquetObjectReader::new(store, meta);
+    let mut stream = ParquetRecordBatchStreamBuilder::new(reader)
+        .await?
+        .build()?;
+    let mut claims = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        claims.extend(record_batch::from_record_batch(&batch)?);
+    }
+    Ok(claims)
+}
+
+/// Claimed amount statistics of a local file read through a memory map, so pages are decoded
+/// straight from the page cache without copying into read buffers.
+pub fn read_reward_file_stat_mmap(path: &str) -> eyre::Result<ClaimedAmountStat> {
+    let file = fs::File::open(Path::new(path))?;
+    // SAFETY: the map is read only, and `ClaimsWriter::create` replaces claims files by
+    // renaming a new file over them, so the mapped file is never truncated or rewritten
+    let mmap = unsafe { Mmap::map(&file)? };
+    read_chunk_stat(Bytes::from_owner(mmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parquet::read_reward_file_stat_arrow;
+    use alloy::primitives::Address;
+    use futures::stream::BoxStream;
+    use object_store::{
+        memory::InMemory, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta,
+        PutMultipartOpts, PutOptions, PutResult,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn synthetic_claims(rows: u64) -> Vec<eigen_types::RewardsClaimed> {
+        (0..rows)
+            .map(|i| eigen_types::RewardsClaimed {
+                root: [(i % 256) as u8; 32],
+                earner: Address::left_padding_from(&(i % 1000).to_be_bytes()).to_checksum(None),
+                claimer: Address::left_padding_from(&i.to_be_bytes()).to_checksum(None),
+                recipient: Address::left_padding_from(&i.to_be_bytes()).to_checksum(None),
+                token: Address::left_padding_from(&(i % 7 + 1).to_be_bytes()).to_checksum(None),
+                claimed_amount: i as u128 * 1_000_000_000,
+                block_number: i / 10,
+                transaction_hash: [(i % 256) as u8; 32],
+                log_index: i % 10,
+                cost: None,
+            })
+            .collect()
+    }
+
+    fn uncompressed() -> Config {
+        Config {
+            dictionary: false,
+            bloom_filter_columns: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    /// Counts the bytes fetched from the wrapped store.
+    #[derive(Debug)]
+    struct CountingStore {
+        inner: InMemory,
+        bytes_read: AtomicUsize,
+    }
+
+    impl std::fmt::Display for CountingStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "CountingStore({})", self.inner)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for CountingStore {
+        async fn put_opts(
+            &self,
+            location: &ObjectPath,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &ObjectPath,
+            opts: PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &ObjectPath,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            let head = options.head;
+            let result = self.inner.get_opts(location, options).await?;
+            if !head {
+                self.bytes_read
+                    .fetch_add(result.range.len(), Ordering::Relaxed);
+            }
+            Ok(result)
+        }
+
+        async fn delete(&self, location: &ObjectPath) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&ObjectPath>,
+        ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&ObjectPath>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &ObjectPath, to: &ObjectPath) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &ObjectPath,
+            to: &ObjectPath,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_round_trip() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = ObjectPath::from("claims/part-00000.parquet");
+        let claims = synthetic_claims(1_000);
+        let metadata = put_claims(
+            store.as_ref(),
+            &path,
+            &claims,
+            &Config::default(),
+            Metadata::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(metadata.row_count, 1_000);
+
+        assert_eq!(read_claims(store.clone(), &path).await.unwrap(), claims);
+        let stat = read_reward_stat(store, &path).await.unwrap();
+        assert_eq!(stat.count, 1_000);
+        assert_eq!(
+            stat.claimed_max,
+            alloy::primitives::U256::from(999_000_000_000u128)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stat_reads_ranges() {
+        let store = Arc::new(CountingStore {
+            inner: InMemory::new(),
+            bytes_read: AtomicUsize::new(0),
+        });
+        let path = ObjectPath::from("claims.parquet");
+        put_claims(
+            store.as_ref(),
+            &path,
+            &synthetic_claims(20_000),
+            &uncompressed(),
+            Metadata::default(),
+        )
+        .await
+        .unwrap();
+        let size = store.head(&path).await.unwrap().size;
+
+        let stat = read_reward_stat(store.clone(), &path).await.unwrap();
+        assert_eq!(stat.count, 20_000);
+        let bytes_read = store.bytes_read.load(Ordering::Relaxed);
+        assert!(bytes_read > 0);
+        assert!(
+            bytes_read < size / 2,
+            "read {} of {} bytes",
+            bytes_read,
+            size
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dataset_stat() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let claims = synthetic_claims(3_000);
+        for (i, chunk) in claims.chunks(1_000).enumerate() {
+            let path = ObjectPath::from(format!("dataset/token=0x01/part-{:05}.parquet", i));
+            put_claims(
+                store.as_ref(),
+                &path,
+                chunk,
+                &Config::default(),
+                Metadata::default(),
+            )
+            .await
+            .unwrap();
+        }
+        store
+            .put(&ObjectPath::from("dataset/_SUCCESS"), PutPayload::new())
+            .await
+            .unwrap();
+
+        let stat = read_dataset_stat(store, &ObjectPath::from("dataset"))
+            .await
+            .unwrap();
+        assert_eq!(stat.count, 3_000);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_claim_cost_stat() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let claims = synthetic_claims(2_000)
+            .into_iter()
+            .enumerate()
+            .map(|(i, claim)| eigen_types::RewardsClaimed {
+                cost: (i % 2 == 0).then_some(eigen_types::ClaimCost {
+                    gas_used: 100_000,
+                    effective_gas_price: 3,
+                    fee_paid: 300_000 + i as u128,
+                }),
+                ..claim
+            })
+            .collect::<Vec<_>>();
+        for (i, chunk) in claims.chunks(1_000).enumerate() {
+            let path = ObjectPath::from(format!("dataset/part-{:05}.parquet", i));
+            put_claims(
+                store.as_ref(),
+                &path,
+                chunk,
+                &Config::default(),
+                Metadata::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let path = ObjectPath::from("dataset/part-00000.parquet");
+        let stat = read_claim_cost_stat(store.clone(), &path).await.unwrap();
+        assert_eq!(stat, ClaimCostStat::from_claims(&claims[..1_000]));
+        let stat = read_dataset_claim_cost_stat(store, &ObjectPath::from("dataset"))
+            .await
+            .unwrap();
+        assert_eq!(stat.count, 1_000);
+        assert_eq!(stat, ClaimCostStat::from_claims(&claims));
+    }
+
+    #[test]
+    fn test_rewrite_leaves_mapped_file() {
+        let path = std::env::temp_dir().join("pq_test_rewrite_mapped.parquet");
+        let path = path.to_str().unwrap();
+        crate::parquet::write_parquet_file(path, &synthetic_claims(1_000)).unwrap();
+        let file = fs::File::open(path).unwrap();
+        let mmap = unsafe { Mmap::map(&file).unwrap() };
+
+        crate::parquet::write_parquet_file(path, &synthetic_claims(10)).unwrap();
+        // the map still holds the whole old file
+        assert_eq!(
+            read_chunk_stat(Bytes::from_owner(mmap)).unwrap().count,
+            1_000
+        );
+        assert_eq!(read_reward_file_stat_mmap(path).unwrap().count, 10);
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+    }
+
+    #[tokio::test]
+    async fn test_local_file_system() {
+        let dir = std::env::temp_dir().join("pq_test_object_store");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let location = dir.join("claims.parquet");
+        let (store, path) = object_store(&format!("file://{}", location.display())).unwrap();
+        let claims = synthetic_claims(500);
+        put_claims(
+            store.as_ref(),
+            &path,
+            &claims,
+            &Config::default(),
+            Metadata::default(),
+        )
+        .await
+        .unwrap();
+
+        // the object is an ordinary file
+        let location = location.to_str().unwrap();
+        assert_eq!(
+            read_reward_stat(store, &path).await.unwrap(),
+            read_reward_file_stat_arrow(location).unwrap()
+        );
+        assert_eq!(
+            read_reward_file_stat_mmap(location).unwrap(),
+            read_reward_file_stat_arrow(location).unwrap()
+        );
+    }
+
+    /// Runs against an S3 compatible service, e.g. MinIO, named by `PQ_S3_TEST_URL` such as
+    /// `s3://pq-test/claims.parquet`, with the `AWS_*` variables set for it.  The README shows
+    /// how to start MinIO for it.
+    #[tokio::test]
+    #[ignore] // needs an S3 compatible service, run with --ignored
+    async fn test_s3_round_trip() {
+        let location = std::env::var("PQ_S3_TEST_URL")
+            .expect("PQ_S3_TEST_URL must name the test object, e.g. s3://pq-test/claims.parquet");
+        let (store, path) = object_store(&location).unwrap();
+        let claims = synthetic_claims(1_000);
+        put_claims(
+            store.as_ref(),
+            &path,
+            &claims,
+            &Config::default(),
+            Metadata::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(read_claims(store.clone(), &path).await.unwrap(), claims);
+        assert_eq!(
+            read_reward_stat(store.clone(), &path).await.unwrap().count,
+            1_000
+        );
+        store.delete(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use alloy::primitives::U256;
+use parquet::{
+    data_type::{BoolType, FixedLenByteArray, FixedLenByteArrayType, Int64Type},
+    file::reader::{FileReader, SerializedFileReader},
+    file::writer::SerializedFileWriter,
+    record::RowAccessor,
+    schema::parser::parse_message_type,
+};
+
+use crate::parquet::{
+    address_from_bytes, amount_from_bytes, amount_to_fixed_bytes, column_index, parse_address,
+    write_column,
+};
+
+/// Total rewards offered by an AVS in a token over a time window.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct RewardsOffered {
+    pub avs: String,
+    pub token: String,
+    pub total: U256,
+}
+
+/// Write rewards submissions as a submissions table and a child strategies table
+/// keyed by `rewardsSubmissionHash`.  Addresses are stored as 20 byte binary, as in the claims
+/// table.
+pub fn write_submissions_parquet_file(
+    submission_path: &str,
+    strategy_path: &str,
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+) -> eyre::Result<()> {
+    let message_type = "
+      message rewards_submission {
+        REQUIRED FIXED_LEN_BYTE_ARRAY (20) avs;
+        REQUIRED INT64 submissionNonce;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (32) rewardsSubmissionHash;
+        REQUIRED BOOLEAN forAll;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (20) token;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (32) amount (DECIMAL(76,0));
+        REQUIRED INT64 startTimestamp;
+        REQUIRED INT64 duration;
+      }
+    ";
+    let schema = Arc::new(parse_message_type(message_type)?);
+    let file = fs::File::create(Path::new(submission_path))?;
+    let mut writer = SerializedFileWriter::new(file, schema, Default::default())?;
+    let mut row_group_writer = writer.next_row_group()?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &address_column(rewards_submissions.iter().map(|s| s.avs.as_str()))?,
+    )?;
+    write_column::<Int64Type>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| s.submission_nonce as i64)
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| FixedLenByteArray::from(s.rewards_submission_hash.to_vec()))
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<BoolType>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| s.for_all)
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &address_column(rewards_submissions.iter().map(|s| s.token.as_str()))?,
+    )?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| amount_to_fixed_bytes(s.amount))
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<Int64Type>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| s.start_timestamp as i64)
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<Int64Type>(
+        &mut row_group_writer,
+        &rewards_submissions
+            .iter()
+            .map(|s| s.duration as i64)
+            .collect::<Vec<_>>(),
+    )?;
+    row_group_writer.close()?;
+    writer.close()?;
+
+    let message_type = "
+      message rewards_submission_strategy {
+        REQUIRED FIXED_LEN_BYTE_ARRAY (32) rewardsSubmissionHash;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (20) strategy;
+        REQUIRED FIXED_LEN_BYTE_ARRAY (32) multiplier (DECIMAL(76,0));
+      }
+    ";
+    let strategies = rewards_submissions
+        .iter()
+        .flat_map(|s| {
+            s.strategies_and_multipliers
+                .iter()
+                .map(|strategy| (s.rewards_submission_hash, strategy))
+        })
+        .collect::<Vec<_>>();
+    let schema = Arc::new(parse_message_type(message_type)?);
+    let file = fs::File::create(Path::new(strategy_path))?;
+    let mut writer = SerializedFileWriter::new(file, schema, Default::default())?;
+    let mut row_group_writer = writer.next_row_group()?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &strategies
+            .iter()
+            .map(|(hash, _)| FixedLenByteArray::from(hash.to_vec()))
+            .collect::<Vec<_>>(),
+    )?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &address_column(strategies.iter().map(|(_, s)| s.strategy.as_str()))?,
+    )?;
+    write_column::<FixedLenByteArrayType>(
+        &mut row_group_writer,
+        &strategies
+            .iter()
+            .map(|(_, s)| amount_to_fixed_bytes(s.multiplier))
+            .collect::<Vec<_>>(),
+    )?;
+    row_group_writer.close()?;
+    writer.close()?;
+
+    Ok(())
+}
+
+/// Validate and encode addresses for a `FIXED_LEN_BYTE_ARRAY (20)` column.
+fn address_column<'a>(
+    addresses: impl Iterator<Item = &'a str>,
+) -> eyre::Result<Vec<FixedLenByteArray>> {
+    addresses
+        .map(|address| Ok(FixedLenByteArray::from(parse_address(address)?.to_vec())))
+        .collect()
+}
+
+/// Total rewards offered per AVS and token for submissions starting in `[from_timestamp, to_timestamp)`.
+pub fn read_rewards_offered(
+    path: &str,
+    from_timestamp: u32,
+    to_timestamp: u32,
+) -> eyre::Result<Vec<RewardsOffered>> {
+    let file = fs::File::open(Path::new(path))?;
+    let reader = SerializedFileReader::new(file)?;
+    let avs_column = column_index(reader.metadata(), "avs")?;
+    let token_column = column_index(reader.metadata(), "token")?;
+    let amount_column = column_index(reader.metadata(), "amount")?;
+    let start_timestamp_column = column_index(reader.metadata(), "startTimestamp")?;
+    let mut totals: BTreeMap<(String, String), U256> = BTreeMap::new();
+    for row in reader.get_row_iter(None)? {
+        let row = row?;
+        let start_timestamp = row.get_long(start_timestamp_column)?;
+        if start_timestamp < from_timestamp as i64 || start_timestamp >= to_timestamp as i64 {
+            continue;
+        }
+        let avs = address_from_bytes(row.get_bytes(avs_column)?.data())?;
+        let token = address_from_bytes(row.get_bytes(token_column)?.data())?;
+        let amount = amount_from_bytes(row.get_decimal(amount_column)?.data())?;
+        *totals.entry((avs, token)).or_default() += amount;
+    }
+    Ok(totals
+        .into_iter()
+        .map(|((avs, token), total)| RewardsOffered { avs, token, total })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(
+        avs: &str,
+        token: &str,
+        amount: u128,
+        start_timestamp: u32,
+    ) -> eigen_types::RewardsSubmission {
+        eigen_types::RewardsSubmission {
+            avs: avs.to_string(),
+            submission_nonce: 0,
+            rewards_submission_hash: [start_timestamp as u8; 32],
+            for_all: false,
+            strategies_and_multipliers: vec![eigen_types::StrategyAndMultiplier {
+                strategy: "0x0000000000000000000000000000000000000003".to_string(),
+                multiplier: 1_000_000_000_000_000_000,
+            }],
+            token: token.to_string(),
+            amount,
+            start_timestamp,
+            duration: 604800,
+        }
+    }
+
+    #[test]
+    fn test_read_rewards_offered() {
+        let dir = std::env::temp_dir();
+        let submission_path = dir.join("pq_test_rewards_submission.parquet");
+        let strategy_path = dir.join("pq_test_rewards_submission_strategy.parquet");
+        let avs = "0x0000000000000000000000000000000000000001";
+        let token = "0x0000000000000000000000000000000000000002";
+        let submissions = vec![
+            submission(avs, token, u128::MAX, 10),
+            submission(avs, token, u128::MAX, 20),
+            submission(avs, token, 7, 30),
+        ];
+        write_submissions_parquet_file(
+            submission_path.to_str().unwrap(),
+            strategy_path.to_str().unwrap(),
+            &submissions,
+        )
+        .unwrap();
+
+        let offered = read_rewards_offered(submission_path.to_str().unwrap(), 0, 30).unwrap();
+        assert_eq!(offered.len(), 1);
+        assert_eq!(offered[0].avs, avs);
+        assert_eq!(offered[0].token, token);
+        assert_eq!(offered[0].total, U256::from(u128::MAX) * U256::from(2));
+
+        let offered = read_rewards_offered(submission_path.to_str().unwrap(), 30, 31).unwrap();
+        assert_eq!(offered[0].total, U256::from(7));
+    }
+
+    #[test]
+    fn test_addresses_stored_as_binary() {
+        let dir = std::env::temp_dir();
+        let submission_path = dir.join("pq_test_submission_addresses.parquet");
+        let strategy_path = dir.join("pq_test_submission_strategy_addresses.parquet");
+        let avs = "0xAcc1fb458a1317E886dB376Fc8141540537E68fE";
+        let token = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+        write_submissions_parquet_file(
+            submission_path.to_str().unwrap(),
+            strategy_path.to_str().unwrap(),
+            &[submission(&avs.to_lowercase(), token, 5, 10)],
+        )
+        .unwrap();
+
+        for (path, columns) in [
+            (&submission_path, &["avs", "token"][..]),
+            (&strategy_path, &["strategy"][..]),
+        ] {
+            let reader = SerializedFileReader::new(fs::File::open(path).unwrap()).unwrap();
+            let schema = reader.metadata().file_metadata().schema_descr_ptr();
+            for column in columns {
+                let column = schema.column(column_index(reader.metadata(), column).unwrap());
+                assert_eq!(
+                    column.physical_type(),
+                    parquet::basic::Type::FIXED_LEN_BYTE_ARRAY
+                );
+                assert_eq!(column.type_length(), 20);
+            }
+        }
+        // read back checksummed
+        let offered = read_rewards_offered(submission_path.to_str().unwrap(), 0, 11).unwrap();
+        assert_eq!(offered[0].avs, avs);
+        assert_eq!(offered[0].token, token);
+
+        assert!(write_submissions_parquet_file(
+            submission_path.to_str().unwrap(),
+            strategy_path.to_str().unwrap(),
+            &[submission("0x1234", token, 5, 10)],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, Write},
+};
+
+use alloy::primitives::{Address, Bytes, B256};
+
+/// A contract log as returned by the node, before decoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+}
+
+/// The largest log data a record may hold.  Log data is paid for in gas, so a block holds far
+/// less, and a corrupt length is refused instead of allocated.
+const MAX_DATA_LEN: usize = 1 << 24;
+
+/// Writes raw logs to a binary archive.  Each record is
+/// `block_number | transaction_hash | log_index | address | topic_count: u8 | topics | data_len: u32 | data`
+/// with integers big-endian.
+pub struct ArchiveWriter {
+    writer: BufWriter<File>,
+}
+
+impl ArchiveWriter {
+    /// Open the archive, appending to it or starting it over.
+    pub fn open(path: &str, append: bool) -> eyre::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        Ok(ArchiveWriter {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Open the archive to continue indexing from `start_block`, first dropping the records of
+    /// that block and later.  The archive is flushed before its batch is committed, so a run
+    /// interrupted in between leaves logs past the checkpoint that the resumed run fetches again.
+    pub fn resume(path: &str, start_block: u64) -> eyre::Result<Self> {
+        let file = match OpenOptions::new().read(true).write(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return ArchiveWriter::open(path, false),
+            Err(e) => return Err(e.into()),
+        };
+        // records are written in block order, and a record cut short by the interruption is
+        // past the checkpoint too
+        let mut reader = BufReader::new(&file);
+        let mut kept_len = 0;
+        loop {
+            match read_record(&mut reader) {
+                Ok(Some(raw_log)) if raw_log.block_number < start_block => {
+                    kept_len = reader.stream_position()?;
+                }
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        file.set_len(kept_len)?;
+        ArchiveWriter::open(path, true)
+    }
+
+    pub fn append(&mut self, raw_log: &RawLog) -> eyre::Result<()> {
+        let topic_count: u8 = raw_log.topics.len().try_into()?;
+        if raw_log.data.len() > MAX_DATA_LEN {
+            return Err(eyre::eyre!(
+                "Log data of {} bytes exceeds {}",
+                raw_log.data.len(),
+                MAX_DATA_LEN
+            ));
+        }
+        let data_len = raw_log.data.len() as u32;
+        self.writer.write_all(&raw_log.block_number.to_be_bytes())?;
+        self.writer.write_all(raw_log.transaction_hash.as_slice())?;
+        self.writer.write_all(&raw_log.log_index.to_be_bytes())?;
+        self.writer.write_all(raw_log.address.as_slice())?;
+        self.writer.write_all(&[topic_count])?;
+        for topic in &raw_log.topics {
+            self.writer.write_all(topic.as_slice())?;
+        }
+        self.writer.write_all(&data_len.to_be_bytes())?;
+        self.writer.write_all(&raw_log.data)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> eyre::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read every raw log in the archive, in the order written.  An archive ending part way
+/// through a record is an error.
+pub fn read_archive(path: &str) -> eyre::Result<Vec<RawLog>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut raw_logs = Vec::new();
+    loop {
+        match read_record(&mut reader) {
+            Ok(Some(raw_log)) => raw_logs.push(raw_log),
+            Ok(None) => return Ok(raw_logs),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Err(eyre::eyre!(
+                    "{} ends in a truncated record after {} records",
+                    path,
+                    raw_logs.len()
+                ))
+            }
+            Err(e) => return Err(eyre::eyre!("Record {} of {}: {}", raw_logs.len(), path, e)),
+        }
+    }
+}
+
+/// Read the next record, or `None` at the end of the archive.  A record cut short fails with
+/// [`ErrorKind::UnexpectedEof`].
+fn read_record(reader: &mut impl Read) -> io::Result<Option<RawLog>> {
+    let mut block_number = [0u8; 8];
+    // only the end of the archive may come before the first byte of a record
+    let mut read = 0;
+    while read < block_number.len() {
+        match reader.read(&mut block_number[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let mut transaction_hash = [0u8; 32];
+    reader.read_exact(&mut transaction_hash)?;
+    let mut log_index = [0u8; 8];
+    reader.read_exact(&mut log_index)?;
+    let mut address = [0u8; 20];
+    reader.read_exact(&mut address)?;
+    let mut topic_count = [0u8; 1];
+    reader.read_exact(&mut topic_count)?;
+    let mut topics = Vec::with_capacity(topic_count[0] as usize);
+    for _ in 0..topic_count[0] {
+        let mut topic = [0u8; 32];
+        reader.read_exact(&mut topic)?;
+        topics.push(B256::from(topic));
+    }
+    let mut data_len = [0u8; 4];
+    reader.read_exact(&mut data_len)?;
+    let data_len = u32::from_be_bytes(data_len) as usize;
+    if data_len > MAX_DATA_LEN {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("data length {} exceeds {}", data_len, MAX_DATA_LEN),
+        ));
+    }
+    let mut data = vec![0u8; data_len];
+    reader.read_exact(&mut data)?;
+    Ok(Some(RawLog {
+        address: Address::from(address),
+        topics,
+        data: data.into(),
+        block_number: u64::from_be_bytes(block_number),
+        transaction_hash: B256::from(transaction_hash),
+        log_index: u64::from_be_bytes(log_index),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let path = std::env::temp_dir().join("reward_json_test_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let raw_logs = vec![
+            RawLog {
+                address: Address::repeat_byte(1),
+                topics: vec![B256::repeat_byte(2), B256::repeat_byte(3)],
+                data: vec![4u8; 96].into(),
+                block_number: 1671211,
+                transaction_hash: B256::repeat_byte(5),
+                log_index: 6,
+            },
+            RawLog {
+                address: Address::repeat_byte(7),
+                topics: vec![],
+                data: Bytes::new(),
+                block_number: 1671212,
+                transaction_hash: B256::repeat_byte(8),
+                log_index: 0,
+            },
+        ];
+        let mut writer = ArchiveWriter::open(path, false).unwrap();
+        writer.append(&raw_logs[0]).unwrap();
+        writer.flush().unwrap();
+        let mut writer = ArchiveWriter::open(path, true).unwrap();
+        writer.append(&raw_logs[1]).unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(read_archive(path).unwrap(), raw_logs);
+    }
+
+    fn raw_log(block_number: u64, log_index: u64) -> RawLog {
+        RawLog {
+            address: Address::repeat_byte(1),
+            topics: vec![B256::repeat_byte(2)],
+            data: vec![3u8; 64].into(),
+            block_number,
+            transaction_hash: B256::repeat_byte(4),
+            log_index,
+        }
+    }
+
+    #[test]
+    fn test_resume_drops_logs_past_checkpoint() {
+        let path = std::env::temp_dir().join("reward_json_test_resume_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let mut writer = ArchiveWriter::open(path, false).unwrap();
+        for raw_log in [
+            raw_log(10, 0),
+            raw_log(10, 1),
+            raw_log(11, 0),
+            raw_log(12, 0),
+        ] {
+            writer.append(&raw_log).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // the batch of blocks 11 and 12 was archived but not committed
+        let mut writer = ArchiveWriter::resume(path, 11).unwrap();
+        writer.append(&raw_log(11, 0)).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(
+            read_archive(path).unwrap(),
+            vec![raw_log(10, 0), raw_log(10, 1), raw_log(11, 0)]
+        );
+
+        // nothing past the checkpoint
+        ArchiveWriter::resume(path, 12).unwrap().flush().unwrap();
+        assert_eq!(read_archive(path).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_truncated_archive() {
+        let path = std::env::temp_dir().join("reward_json_test_truncated_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let mut writer = ArchiveWriter::open(path, false).unwrap();
+        writer.append(&raw_log(10, 0)).unwrap();
+        writer.append(&raw_log(11, 0)).unwrap();
+        writer.flush().unwrap();
+        let len = std::fs::metadata(path).unwrap().len();
+        let record_len = len / 2;
+
+        // cut inside the block number and inside the data of the second record
+        for cut in [record_len + 3, len - 1] {
+            let file = OpenOptions::new().write(true).open(path).unwrap();
+            file.set_len(cut).unwrap();
+            let error = read_archive(path).unwrap_err().to_string();
+            assert!(
+                error.contains("truncated record after 1 records"),
+                "{}",
+                error
+            );
+
+            // resuming drops the partial record with the rest of the uncommitted logs
+            ArchiveWriter::resume(path, 11).unwrap().flush().unwrap();
+            assert_eq!(read_archive(path).unwrap(), vec![raw_log(10, 0)]);
+
+            let mut writer = ArchiveWriter::open(path, true).unwrap();
+            writer.append(&raw_log(11, 0)).unwrap();
+            writer.flush().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_corrupt_data_len() {
+        let path = std::env::temp_dir().join("reward_json_test_corrupt_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let mut writer = ArchiveWriter::open(path, false).unwrap();
+        writer.append(&raw_log(10, 0)).unwrap();
+        writer.flush().unwrap();
+        // data_len sits before the 64 bytes of data
+        let mut bytes = std::fs::read(path).unwrap();
+        let data_len = bytes.len() - 64 - 4;
+        bytes[data_len..data_len + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        std::fs::write(path, bytes).unwrap();
+        let error = read_archive(path).unwrap_err().to_string();
+        assert!(
+            error.contains("data length 4294967295 exceeds"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_oversized_data_refused() {
+        let path = std::env::temp_dir().join("reward_json_test_oversized_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let mut writer = ArchiveWriter::open(path, false).unwrap();
+        let raw_log = RawLog {
+            data: vec![0u8; MAX_DATA_LEN + 1].into(),
+            ..raw_log(10, 0)
+        };
+        assert!(writer.append(&raw_log).is_err());
+    }
+
+    #[test]
+    fn test_resume_without_archive() {
+        let path = std::env::temp_dir().join("reward_json_test_resume_missing_raw_logs.bin");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let mut writer = ArchiveWriter::resume(path, 11).unwrap();
+        writer.append(&raw_log(11, 0)).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(read_archive(path).unwrap(), vec![raw_log(11, 0)]);
+    }
+}
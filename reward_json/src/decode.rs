@@ -0,0 +1,217 @@
+use alloy::{
+    primitives::{Address, B256, U256},
+    sol,
+    sol_types::SolEvent,
+};
+
+use crate::archive::RawLog;
+
+sol!(IRewardsCoordinator, "abi/IRewardsCoordinator.json");
+
+/// A decoded rewards coordinator event.
+#[derive(Debug)]
+pub enum RewardsEvent {
+    Claimed(eigen_types::RewardsClaimed),
+    Submission(eigen_types::RewardsSubmission),
+}
+
+/// The topic0 of every event the indexer decodes.
+pub fn event_signatures() -> Vec<B256> {
+    vec![
+        IRewardsCoordinator::RewardsClaimed::SIGNATURE_HASH,
+        IRewardsCoordinator::AVSRewardsSubmissionCreated::SIGNATURE_HASH,
+        IRewardsCoordinator::RewardsSubmissionForAllCreated::SIGNATURE_HASH,
+    ]
+}
+
+/// Decode a raw log, or `None` if it is not an event the indexer keeps.
+pub fn decode_raw_log(raw_log: &RawLog) -> eyre::Result<Option<RewardsEvent>> {
+    let Some(topic0) = raw_log.topics.first() else {
+        return Ok(None);
+    };
+    let topics = raw_log.topics.iter().copied();
+    if *topic0 == IRewardsCoordinator::RewardsClaimed::SIGNATURE_HASH {
+        let event =
+            IRewardsCoordinator::RewardsClaimed::decode_raw_log(topics, &raw_log.data, true)?;
+        let root: [u8; 32] = event.root.as_slice().try_into()?;
+        Ok(Some(RewardsEvent::Claimed(eigen_types::RewardsClaimed {
+            root,
+            earner: event.earner.to_string(),
+            claimer: event.claimer.to_string(),
+            recipient: event.recipient.to_string(),
+            token: event.token.to_string(),
+            claimed_amount: narrow(event.claimedAmount, "claimedAmount")?,
+            block_number: raw_log.block_number,
+            transaction_hash: raw_log.transaction_hash.0,
+            log_index: raw_log.log_index,
+            cost: None,
+        })))
+    } else if *topic0 == IRewardsCoordinator::AVSRewardsSubmissionCreated::SIGNATURE_HASH {
+        let event = IRewardsCoordinator::AVSRewardsSubmissionCreated::decode_raw_log(
+            topics,
+            &raw_log.data,
+            true,
+        )?;
+        Ok(Some(RewardsEvent::Submission(to_rewards_submission(
+            event.avs,
+            event.submissionNonce,
+            event.rewardsSubmissionHash,
+            &event.rewardsSubmission,
+            false,
+        )?)))
+    } else if *topic0 == IRewardsCoordinator::RewardsSubmissionForAllCreated::SIGNATURE_HASH {
+        let event = IRewardsCoordinator::RewardsSubmissionForAllCreated::decode_raw_log(
+            topics,
+            &raw_log.data,
+            true,
+        )?;
+        Ok(Some(RewardsEvent::Submission(to_rewards_submission(
+            event.submitter,
+            event.submissionNonce,
+            event.rewardsSubmissionHash,
+            &event.rewardsSubmission,
+            true,
+        )?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A `uint256` event field as the narrower integer it is stored as.  Values that do not fit are
+/// an error rather than a panic, so one outlying event cannot stop the indexer.
+fn narrow<T: TryFrom<U256>>(value: U256, field: &str) -> eyre::Result<T> {
+    T::try_from(value).map_err(|_| eyre::eyre!("{} out of range: {}", field, value))
+}
+
+/// Flatten a rewards submission event into its storage representation.
+fn to_rewards_submission(
+    avs: Address,
+    submission_nonce: U256,
+    rewards_submission_hash: B256,
+    rewards_submission: &IRewardsCoordinator::RewardsSubmission,
+    for_all: bool,
+) -> eyre::Result<eigen_types::RewardsSubmission> {
+    let rewards_submission_hash: [u8; 32] = rewards_submission_hash.as_slice().try_into()?;
+    let strategies_and_multipliers = rewards_submission
+        .strategiesAndMultipliers
+        .iter()
+        .map(|s| eigen_types::StrategyAndMultiplier {
+            strategy: s.strategy.to_string(),
+            multiplier: s.multiplier,
+        })
+        .collect();
+    Ok(eigen_types::RewardsSubmission {
+        avs: avs.to_string(),
+        submission_nonce: narrow(submission_nonce, "submissionNonce")?,
+        rewards_submission_hash,
+        for_all,
+        strategies_and_multipliers,
+        token: rewards_submission.token.to_string(),
+        amount: narrow(rewards_submission.amount, "amount")?,
+        start_timestamp: rewards_submission.startTimestamp,
+        duration: rewards_submission.duration,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_rewards_claimed() {
+        let event = IRewardsCoordinator::RewardsClaimed {
+            root: B256::repeat_byte(1),
+            earner: Address::repeat_byte(2),
+            claimer: Address::repeat_byte(3),
+            recipient: Address::repeat_byte(4),
+            token: Address::repeat_byte(5),
+            claimedAmount: U256::from(1_000_000_000_000_000_000u128),
+        };
+        let log_data = event.encode_log_data();
+        let raw_log = RawLog {
+            address: Address::repeat_byte(6),
+            topics: log_data.topics().to_vec(),
+            data: log_data.data,
+            block_number: 7,
+            transaction_hash: B256::repeat_byte(8),
+            log_index: 9,
+        };
+        let Some(RewardsEvent::Claimed(claimed)) = decode_raw_log(&raw_log).unwrap() else {
+            panic!("expected RewardsClaimed");
+        };
+        assert_eq!(claimed.root, [1; 32]);
+        assert_eq!(claimed.earner, Address::repeat_byte(2).to_string());
+        assert_eq!(claimed.token, Address::repeat_byte(5).to_string());
+        assert_eq!(claimed.claimed_amount, 1_000_000_000_000_000_000);
+        assert_eq!(claimed.block_number, 7);
+        assert_eq!(claimed.transaction_hash, [8; 32]);
+        assert_eq!(claimed.log_index, 9);
+    }
+
+    fn submission_log(submission_nonce: U256, amount: U256) -> eyre::Result<Option<RewardsEvent>> {
+        let event = IRewardsCoordinator::AVSRewardsSubmissionCreated {
+            avs: Address::repeat_byte(1),
+            submissionNonce: submission_nonce,
+            rewardsSubmissionHash: B256::repeat_byte(2),
+            rewardsSubmission: IRewardsCoordinator::RewardsSubmission {
+                strategiesAndMultipliers: vec![IRewardsCoordinator::StrategyAndMultiplier {
+                    strategy: Address::repeat_byte(3),
+                    multiplier: 1_000_000_000_000_000_000,
+                }],
+                token: Address::repeat_byte(4),
+                amount,
+                startTimestamp: 1720656000,
+                duration: 604800,
+            },
+        };
+        let log_data = event.encode_log_data();
+        decode_raw_log(&RawLog {
+            address: Address::repeat_byte(6),
+            topics: log_data.topics().to_vec(),
+            data: log_data.data,
+            block_number: 7,
+            transaction_hash: B256::repeat_byte(8),
+            log_index: 9,
+        })
+    }
+
+    #[test]
+    fn test_decode_rewards_submission() {
+        let Some(RewardsEvent::Submission(submission)) =
+            submission_log(U256::from(3), U256::from(u128::MAX)).unwrap()
+        else {
+            panic!("expected RewardsSubmission");
+        };
+        assert_eq!(submission.avs, Address::repeat_byte(1).to_string());
+        assert_eq!(submission.submission_nonce, 3);
+        assert_eq!(submission.amount, u128::MAX);
+        assert_eq!(
+            submission.strategies_and_multipliers[0].multiplier,
+            1_000_000_000_000_000_000
+        );
+        assert!(!submission.for_all);
+    }
+
+    #[test]
+    fn test_decode_out_of_range_is_an_error() {
+        let error =
+            submission_log(U256::from(3), U256::from(u128::MAX) + U256::from(1)).unwrap_err();
+        assert!(error.to_string().contains("amount out of range"));
+        let error =
+            submission_log(U256::from(u64::MAX) + U256::from(1), U256::from(5)).unwrap_err();
+        assert!(error.to_string().contains("submissionNonce out of range"));
+    }
+
+    #[test]
+    fn test_decode_unknown_event() {
+        let raw_log = RawLog {
+            address: Address::repeat_byte(6),
+            topics: vec![B256::repeat_byte(1)],
+            data: Default::default(),
+            block_number: 7,
+            transaction_hash: B256::repeat_byte(8),
+            log_index: 9,
+        };
+        assert!(decode_raw_log(&raw_log).unwrap().is_none());
+    }
+}
@@ -1,78 +1,216 @@
+mod archive;
 mod config;
+mod decode;
+mod receipts;
+mod sink;
+mod transfer;
 
 use alloy::{
-    primitives::Address,
+    primitives::{Address, B256},
     providers::{Provider, ProviderBuilder},
-    sol,
+    rpc::types::{Filter, Log},
 };
+use decode::RewardsEvent;
+use serde::Serialize;
 use std::fs::OpenOptions;
 use std::io::Write;
 use tokio::time::{sleep, Duration};
 use tracing::{debug, info};
 
-sol!(
-    #[sol(rpc)]
-    IRewardsCoordinator,
-    "abi/IRewardsCoordinator.json"
-);
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     init_logging();
 
     let config = config::load_config()?;
 
+    match std::env::args().nth(1).as_deref() {
+        None | Some("index") => index(&config).await?,
+        Some("redecode") => redecode(&config)?,
+        Some(command) => return Err(format!("Unknown command: {}", command).into()),
+    }
+
+    Ok(())
+}
+
+/// The typed datasets decoded from the rewards coordinator logs.
+#[derive(Debug, Default)]
+pub struct Datasets {
+    claimed_events: Vec<eigen_types::RewardsClaimed>,
+    submission_events: Vec<eigen_types::RewardsSubmission>,
+}
+
+impl Datasets {
+    fn push(&mut self, event: RewardsEvent) {
+        match event {
+            RewardsEvent::Claimed(claimed) => self.claimed_events.push(claimed),
+            RewardsEvent::Submission(submission) => self.submission_events.push(submission),
+        }
+    }
+
+    fn append(&mut self, mut other: Datasets) {
+        self.claimed_events.append(&mut other.claimed_events);
+        self.submission_events.append(&mut other.submission_events);
+    }
+
+    fn write(&self) -> eyre::Result<()> {
+        write_json("rewards_claimed.json", &self.claimed_events)?;
+        info!(
+            "Wrote {} events to rewards_claimed.json",
+            self.claimed_events.len()
+        );
+        write_json("rewards_submissions.json", &self.submission_events)?;
+        info!(
+            "Wrote {} events to rewards_submissions.json",
+            self.submission_events.len()
+        );
+        Ok(())
+    }
+}
+
+/// Fetch the coordinator logs from the node, archive them raw and load the decoded events
+/// into the configured sinks, or the JSON datasets when there are none.
+async fn index(config: &config::Config) -> eyre::Result<()> {
     let provider = ProviderBuilder::new().on_http(config.rpc_url.parse()?);
-    let contract_address = Address::parse_checksummed(config.rewards_coordinator, None)?;
-    let latest_block = provider.clone().get_block_number().await?;
-    let rewards_contract = IRewardsCoordinator::new(contract_address, provider);
+    let contract_address = Address::parse_checksummed(&config.rewards_coordinator, None)?;
+    let latest_block = provider.get_block_number().await?;
 
-    let mut outputfile = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open("rewards_claimed.json")?;
+    let mut sinks = Vec::new();
+    for sink_config in &config.sinks {
+        sinks.push(sink::Sink::open(sink_config).await?);
+    }
+    let start_block = sink::resume_block(&sinks, config.earliest_block);
+    if start_block > config.earliest_block {
+        info!("Resuming from block {}", start_block);
+    }
+    let (sender, writer) = sink::spawn_writer(sinks, config.sink_channel_capacity);
 
-    let mut claimed_events: Vec<eigen_types::RewardsClaimed> = Vec::new();
-    for block in (config.earliest_block..latest_block).step_by(config.max_blocks as usize) {
+    let mut archive = if start_block > config.earliest_block {
+        archive::ArchiveWriter::resume(&config.raw_log_archive, start_block)?
+    } else {
+        archive::ArchiveWriter::open(&config.raw_log_archive, false)?
+    };
+    let mut datasets = Datasets::default();
+    let mut quality_report = transfer::DataQualityReport::default();
+    for block in (start_block..=latest_block).step_by(config.max_blocks as usize) {
         let begin_block = block;
-        let end_block = core::cmp::min(block + config.max_blocks, latest_block);
-        let events = rewards_contract
-            .RewardsClaimed_filter()
+        let end_block = core::cmp::min(block + config.max_blocks - 1, latest_block);
+        let filter = Filter::new()
+            .address(contract_address)
+            .event_signature(decode::event_signatures())
             .from_block(begin_block)
-            .to_block(end_block)
-            .query()
-            .await?;
+            .to_block(end_block);
+        let logs = provider.get_logs(&filter).await?;
 
+        let mut batch = Datasets::default();
         let mut event_count = 0;
-        for (event, _) in events {
-            let root: [u8; 32] = event.root.as_slice().try_into()?;
-            let rewards_claimed = eigen_types::RewardsClaimed {
-                root,
-                earner: event.earner.to_string(),
-                claimer: event.claimer.to_string(),
-                recipient: event.recipient.to_string(),
-                token: event.token.to_string(),
-                claimed_amount: event.claimedAmount.to::<u128>(),
-            };
-            claimed_events.push(rewards_claimed);
-            event_count += 1;
+        for log in logs {
+            let raw_log = to_raw_log(&log);
+            archive.append(&raw_log)?;
+            if let Some(event) = decode::decode_raw_log(&raw_log)? {
+                batch.push(event);
+                event_count += 1;
+            }
         }
         if event_count > 0 {
             info!("Block {}: {} events", block, event_count);
         } else {
             debug!("Block {}: no events", block);
         }
+        // the claims of a transaction share its block, so each batch holds all of them
+        if config.verify_transfers || config.claim_costs {
+            let receipts = receipts::fetch_receipts(
+                &provider,
+                batch
+                    .claimed_events
+                    .iter()
+                    .map(|c| B256::from(c.transaction_hash)),
+            )
+            .await?;
+            if config.verify_transfers {
+                quality_report.merge(transfer::check_transfers(
+                    contract_address,
+                    &batch.claimed_events,
+                    &receipts::logs_by_transaction(&receipts),
+                )?);
+            }
+            if config.claim_costs {
+                receipts::add_claim_costs(
+                    &mut batch.claimed_events,
+                    &receipts::transaction_costs(&receipts)?,
+                )?;
+            }
+        }
+        // the archive must hold every log before its batch is committed
+        archive.flush()?;
+        // without sinks the datasets are only written as JSON at the end
+        if config.sinks.is_empty() {
+            datasets.append(batch);
+        } else {
+            sender
+                .send(sink::Batch {
+                    datasets: batch,
+                    end_block,
+                })
+                .await?;
+        }
         sleep(Duration::from_millis(config.sleep_duration_ms)).await;
     }
-    let json_file = serde_json::to_string_pretty(&claimed_events)?;
-    outputfile.write_all(json_file.as_bytes())?;
-    outputfile.flush()?;
+    drop(sender);
+    writer.await??;
+    if config.sinks.is_empty() {
+        datasets.write()?;
+    }
+
+    if config.verify_transfers {
+        info!(
+            "Transfer check: {} of {} claims matched, {} flagged",
+            quality_report.matched,
+            quality_report.checked,
+            quality_report.flagged.len()
+        );
+        write_json("claims_quality_report.json", &quality_report)?;
+    }
+
+    Ok(())
+}
+
+/// Rebuild the decoded datasets from the raw log archive without network access.
+fn redecode(config: &config::Config) -> eyre::Result<()> {
+    let raw_logs = archive::read_archive(&config.raw_log_archive)?;
     info!(
-        "Wrote {} events to rewards_claimed.json",
-        claimed_events.len()
+        "Read {} raw logs from {}",
+        raw_logs.len(),
+        config.raw_log_archive
     );
+    let mut datasets = Datasets::default();
+    for raw_log in &raw_logs {
+        if let Some(event) = decode::decode_raw_log(raw_log)? {
+            datasets.push(event);
+        }
+    }
+    datasets.write()
+}
+
+fn to_raw_log(log: &Log) -> archive::RawLog {
+    archive::RawLog {
+        address: log.inner.address,
+        topics: log.inner.data.topics().to_vec(),
+        data: log.inner.data.data.clone(),
+        block_number: log.block_number.unwrap_or_default(),
+        transaction_hash: log.transaction_hash.unwrap_or_default(),
+        log_index: log.log_index.unwrap_or_default(),
+    }
+}
 
+fn write_json<T: Serialize + ?Sized>(path: &str, value: &T) -> eyre::Result<()> {
+    let mut outputfile = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let json_file = serde_json::to_string_pretty(value)?;
+    outputfile.write_all(json_file.as_bytes())?;
+    outputfile.flush()?;
     Ok(())
 }
 
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::{Log, B256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+    transports::Transport,
+};
+use tracing::{debug, warn};
+
+/// Fetch the receipt of each distinct transaction, keyed by transaction hash.
+pub async fn fetch_receipts<T, P>(
+    provider: &P,
+    transaction_hashes: impl IntoIterator<Item = B256>,
+) -> eyre::Result<HashMap<B256, TransactionReceipt>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut receipts = HashMap::new();
+    for transaction_hash in transaction_hashes {
+        if receipts.contains_key(&transaction_hash) {
+            continue;
+        }
+        match provider.get_transaction_receipt(transaction_hash).await? {
+            Some(receipt) => {
+                receipts.insert(transaction_hash, receipt);
+            }
+            None => warn!("No receipt for transaction {}", transaction_hash),
+        }
+    }
+    debug!("Fetched {} receipts", receipts.len());
+    Ok(receipts)
+}
+
+/// The gas used and effective gas price in wei of each transaction with a fetched receipt.
+pub fn transaction_costs(
+    receipts: &HashMap<B256, TransactionReceipt>,
+) -> eyre::Result<HashMap<B256, (u64, u128)>> {
+    receipts
+        .iter()
+        .map(|(transaction_hash, receipt)| {
+            let gas_used = u64::try_from(receipt.gas_used).map_err(|_| {
+                eyre::eyre!(
+                    "Gas used {} out of range in transaction {}",
+                    receipt.gas_used,
+                    transaction_hash
+                )
+            })?;
+            Ok((*transaction_hash, (gas_used, receipt.effective_gas_price)))
+        })
+        .collect()
+}
+
+/// Attach its cost to each claim whose transaction cost is known.  A transaction's fee is
+/// split evenly between the claims it emitted, which share one batch as they share a block;
+/// the remainder of the division is dropped.
+pub fn add_claim_costs(
+    claims: &mut [eigen_types::RewardsClaimed],
+    transaction_costs: &HashMap<B256, (u64, u128)>,
+) -> eyre::Result<()> {
+    let mut claims_in_transaction: HashMap<B256, u128> = HashMap::new();
+    for claim in claims.iter() {
+        *claims_in_transaction
+            .entry(B256::from(claim.transaction_hash))
+            .or_default() += 1;
+    }
+    for claim in claims {
+        let transaction_hash = B256::from(claim.transaction_hash);
+        let Some(&(gas_used, effective_gas_price)) = transaction_costs.get(&transaction_hash)
+        else {
+            continue;
+        };
+        let fee = u128::from(gas_used)
+            .checked_mul(effective_gas_price)
+            .ok_or_else(|| eyre::eyre!("Fee overflows in transaction {}", transaction_hash))?;
+        claim.cost = Some(eigen_types::ClaimCost {
+            gas_used,
+            effective_gas_price,
+            fee_paid: fee / claims_in_transaction[&transaction_hash],
+        });
+    }
+    Ok(())
+}
+
+/// The logs emitted by each transaction.
+pub fn logs_by_transaction(
+    receipts: &HashMap<B256, TransactionReceipt>,
+) -> HashMap<B256, Vec<Log>> {
+    receipts
+        .iter()
+        .map(|(transaction_hash, receipt)| {
+            let logs = receipt
+                .inner
+                .logs()
+                .iter()
+                .map(|log| log.inner.clone())
+                .collect();
+            (*transaction_hash, logs)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(transaction_hash: [u8; 32], log_index: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [0; 32],
+            earner: "0x124".to_string(),
+            claimer: "0x125".to_string(),
+            recipient: "0x126".to_string(),
+            token: "0x127".to_string(),
+            claimed_amount: 1,
+            block_number: 1,
+            transaction_hash,
+            log_index,
+            cost: None,
+        }
+    }
+
+    #[test]
+    fn test_add_claim_costs() {
+        let mut claims = vec![claim([1; 32], 0), claim([1; 32], 1), claim([2; 32], 0)];
+        let costs = HashMap::from([(B256::from([1; 32]), (100_001, 3))]);
+        add_claim_costs(&mut claims, &costs).unwrap();
+        let cost = eigen_types::ClaimCost {
+            gas_used: 100_001,
+            effective_gas_price: 3,
+            fee_paid: 150_001,
+        };
+        assert_eq!(claims[0].cost, Some(cost.clone()));
+        assert_eq!(claims[1].cost, Some(cost));
+        // no receipt
+        assert_eq!(claims[2].cost, None);
+    }
+
+    #[test]
+    fn test_add_claim_costs_overflow() {
+        let mut claims = vec![claim([1; 32], 0)];
+        let costs = HashMap::from([(B256::from([1; 32]), (2, u128::MAX))]);
+        assert!(add_claim_costs(&mut claims, &costs).is_err());
+        assert_eq!(claims[0].cost, None);
+    }
+}
@@ -0,0 +1,209 @@
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::debug;
+
+use crate::config::SinkConfig;
+use crate::Datasets;
+
+/// The events indexed from a block range, committed with `end_block` as the checkpoint.
+#[derive(Debug)]
+pub struct Batch {
+    pub datasets: Datasets,
+    pub end_block: u64,
+}
+
+enum Backend {
+    Parquet(pq::sink::ParquetSink),
+    Postgres(pg::postgres::PostgresClient),
+    Duckdb(duckdb_store::quack::DuckdbClient),
+    Sqlite(sqlite::sq::SqliteClient),
+}
+
+/// A storage backend and the last block committed to it.
+pub struct Sink {
+    backend: Backend,
+    checkpoint: Option<u64>,
+}
+
+impl Sink {
+    /// Open a sink, applying any pending schema migrations first.
+    pub async fn open(config: &SinkConfig) -> eyre::Result<Sink> {
+        let (backend, checkpoint) = match config {
+            SinkConfig::Parquet { path } => {
+                let sink = pq::sink::ParquetSink::open(path)?;
+                let checkpoint = sink.checkpoint()?;
+                (Backend::Parquet(sink), checkpoint)
+            }
+            SinkConfig::Postgres { connection } => {
+                let mut client = pg::postgres::init_postgres(connection).await?;
+                pg::postgres::migrate_up(&mut client).await?;
+                let checkpoint = pg::postgres::read_checkpoint(&mut client).await?;
+                (Backend::Postgres(client), checkpoint)
+            }
+            SinkConfig::Duckdb { path } => {
+                let mut client = duckdb_store::quack::open(path).await?;
+                duckdb_store::quack::migrate_up(&mut client).await?;
+                let checkpoint = duckdb_store::quack::read_checkpoint(&mut client).await?;
+                (Backend::Duckdb(client), checkpoint)
+            }
+            SinkConfig::Sqlite { path } => {
+                let mut client = sqlite::sq::open(path).await?;
+                sqlite::sq::migrate_up(&mut client).await?;
+                let checkpoint = sqlite::sq::read_checkpoint(&mut client).await?;
+                (Backend::Sqlite(client), checkpoint)
+            }
+        };
+        Ok(Sink {
+            backend,
+            checkpoint,
+        })
+    }
+
+    pub fn checkpoint(&self) -> Option<u64> {
+        self.checkpoint
+    }
+
+    /// Write a batch and its checkpoint atomically.  Batches already committed are skipped.
+    pub async fn write(&mut self, batch: &Batch) -> eyre::Result<()> {
+        if self.checkpoint.is_some_and(|c| batch.end_block <= c) {
+            return Ok(());
+        }
+        let claimed = &batch.datasets.claimed_events;
+        let submissions = &batch.datasets.submission_events;
+        match &mut self.backend {
+            Backend::Parquet(sink) => sink.write_batch(claimed, submissions, batch.end_block)?,
+            Backend::Postgres(client) => {
+                pg::postgres::write_batch(client, claimed, submissions, batch.end_block).await?
+            }
+            Backend::Duckdb(client) => {
+                duckdb_store::quack::write_batch(client, claimed, submissions, batch.end_block)
+                    .await?
+            }
+            Backend::Sqlite(client) => {
+                sqlite::sq::write_batch(client, claimed, submissions, batch.end_block).await?
+            }
+        }
+        self.checkpoint = Some(batch.end_block);
+        Ok(())
+    }
+}
+
+/// The block to resume indexing from: one past the least advanced sink's checkpoint.
+pub fn resume_block(sinks: &[Sink], earliest_block: u64) -> u64 {
+    match sinks.iter().map(Sink::checkpoint).min() {
+        Some(Some(checkpoint)) => checkpoint + 1,
+        _ => earliest_block,
+    }
+}
+
+/// Spawn a task writing each batch to every sink.  Sending waits while `capacity` batches are
+/// queued, so indexing cannot run ahead of the slowest sink.
+pub fn spawn_writer(
+    mut sinks: Vec<Sink>,
+    capacity: usize,
+) -> (mpsc::Sender<Batch>, JoinHandle<eyre::Result<()>>) {
+    let (sender, mut receiver) = mpsc::channel::<Batch>(capacity);
+    let writer = tokio::spawn(async move {
+        while let Some(batch) = receiver.recv().await {
+            for sink in sinks.iter_mut() {
+                sink.write(&batch).await?;
+            }
+            debug!("Committed block {}", batch.end_block);
+        }
+        Ok(())
+    });
+    (sender, writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(block_number: u64) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            claimer: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            recipient: "0xAcc1fb458a1317E886dB376Fc8141540537E68fE".to_string(),
+            token: "0x3B78576F7D6837500bA3De27A60c7f594934027E".to_string(),
+            claimed_amount: 1,
+            block_number,
+            transaction_hash: [block_number as u8; 32],
+            log_index: 0,
+            cost: None,
+        }
+    }
+
+    fn batch(end_block: u64) -> Batch {
+        Batch {
+            datasets: Datasets {
+                claimed_events: vec![claim(end_block)],
+                submission_events: vec![],
+            },
+            end_block,
+        }
+    }
+
+    async fn parquet_sink(dir: &std::path::Path) -> Sink {
+        Sink::open(&SinkConfig::Parquet {
+            path: dir.to_str().unwrap().to_string(),
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resume_block() {
+        let dir = std::env::temp_dir().join("reward_json_test_resume_block");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(resume_block(&[], 5), 5);
+
+        let mut behind = parquet_sink(&dir.join("behind")).await;
+        assert_eq!(resume_block(std::slice::from_ref(&behind), 5), 5);
+        behind.write(&batch(9)).await.unwrap();
+        let mut ahead = parquet_sink(&dir.join("ahead")).await;
+        ahead.write(&batch(9)).await.unwrap();
+        ahead.write(&batch(19)).await.unwrap();
+        assert_eq!(resume_block(&[behind, ahead], 5), 10);
+
+        // a sink with no checkpoint starts over
+        let sinks = [
+            parquet_sink(&dir.join("ahead")).await,
+            parquet_sink(&dir.join("new")).await,
+        ];
+        assert_eq!(resume_block(&sinks, 5), 5);
+    }
+
+    #[tokio::test]
+    async fn test_parquet_sink_resume() {
+        let dir = std::env::temp_dir().join("reward_json_test_parquet_sink_resume");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (sender, writer) = spawn_writer(vec![parquet_sink(&dir).await], 1);
+        sender.send(batch(9)).await.unwrap();
+        sender.send(batch(19)).await.unwrap();
+        drop(sender);
+        writer.await.unwrap().unwrap();
+
+        let sinks = vec![parquet_sink(&dir).await];
+        assert_eq!(resume_block(&sinks, 0), 20);
+        // batches already committed, as a rerun from an older sink sends them, are skipped
+        let (sender, writer) = spawn_writer(sinks, 1);
+        sender.send(batch(19)).await.unwrap();
+        sender.send(batch(29)).await.unwrap();
+        drop(sender);
+        writer.await.unwrap().unwrap();
+
+        let mut claims = Vec::new();
+        for part in [
+            "part-000000000009",
+            "part-000000000019",
+            "part-000000000029",
+        ] {
+            let path = dir.join(part).join("rewards_claimed.parquet");
+            claims.extend(pq::parquet::read_reward_file(path.to_str().unwrap()).unwrap());
+        }
+        assert_eq!(claims, vec![claim(9), claim(19), claim(29)]);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 4);
+        assert_eq!(parquet_sink(&dir).await.checkpoint(), Some(29));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy::{
+    primitives::{Address, Log, B256, U256},
+    sol,
+    sol_types::SolEvent,
+};
+use serde::Serialize;
+
+sol! {
+    interface IERC20 {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
+
+/// Why a claim failed the transfer cross-check.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum TransferIssue {
+    /// The receipt of the claim transaction could not be fetched.
+    MissingReceipt,
+    /// No token transfer from the coordinator to the recipient.
+    MissingTransfer,
+    /// A token transfer to the recipient exists, but for a different amount.
+    AmountMismatch { transferred: U256 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlaggedClaim {
+    pub claim: eigen_types::RewardsClaimed,
+    pub issue: TransferIssue,
+}
+
+/// The result of matching every claim against the token transfers in its transaction.
+#[derive(Debug, Default, Serialize)]
+pub struct DataQualityReport {
+    pub checked: u64,
+    pub matched: u64,
+    pub flagged: Vec<FlaggedClaim>,
+}
+
+impl DataQualityReport {
+    /// Add the claims checked in `other`, such as a later batch.
+    pub fn merge(&mut self, other: DataQualityReport) {
+        self.checked += other.checked;
+        self.matched += other.matched;
+        self.flagged.extend(other.flagged);
+    }
+}
+
+/// Match each claim to a `Transfer` of `claimedAmount` from the coordinator to the recipient
+/// in the same transaction.  A transfer log is matched to at most one claim.
+pub fn check_transfers(
+    coordinator: Address,
+    claims: &[eigen_types::RewardsClaimed],
+    logs_by_transaction: &HashMap<B256, Vec<Log>>,
+) -> eyre::Result<DataQualityReport> {
+    let mut report = DataQualityReport::default();
+    let mut matched_logs: HashSet<(B256, usize)> = HashSet::new();
+    for claim in claims {
+        report.checked += 1;
+        let transaction_hash = B256::from(claim.transaction_hash);
+        let issue = match logs_by_transaction.get(&transaction_hash) {
+            Some(logs) => match_transfer(
+                coordinator,
+                claim,
+                transaction_hash,
+                logs,
+                &mut matched_logs,
+            )?,
+            None => Some(TransferIssue::MissingReceipt),
+        };
+        match issue {
+            Some(issue) => report.flagged.push(FlaggedClaim {
+                claim: claim.clone(),
+                issue,
+            }),
+            None => report.matched += 1,
+        }
+    }
+    Ok(report)
+}
+
+fn match_transfer(
+    coordinator: Address,
+    claim: &eigen_types::RewardsClaimed,
+    transaction_hash: B256,
+    logs: &[Log],
+    matched_logs: &mut HashSet<(B256, usize)>,
+) -> eyre::Result<Option<TransferIssue>> {
+    let token: Address = claim.token.parse()?;
+    let recipient: Address = claim.recipient.parse()?;
+    let claimed_amount = U256::from(claim.claimed_amount);
+    let mut transferred = None;
+    for (index, log) in logs.iter().enumerate() {
+        if log.address != token || matched_logs.contains(&(transaction_hash, index)) {
+            continue;
+        }
+        let Ok(transfer) = IERC20::Transfer::decode_log_data(&log.data, true) else {
+            continue;
+        };
+        if transfer.from != coordinator || transfer.to != recipient {
+            continue;
+        }
+        if transfer.value == claimed_amount {
+            matched_logs.insert((transaction_hash, index));
+            return Ok(None);
+        }
+        transferred.get_or_insert(transfer.value);
+    }
+    Ok(Some(match transferred {
+        Some(transferred) => TransferIssue::AmountMismatch { transferred },
+        None => TransferIssue::MissingTransfer,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COORDINATOR: Address = Address::repeat_byte(0xaa);
+    const TOKEN: Address = Address::repeat_byte(0xbb);
+    const RECIPIENT: Address = Address::repeat_byte(0xcc);
+
+    fn claim(transaction_hash: [u8; 32], claimed_amount: u128) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [0; 32],
+            earner: RECIPIENT.to_string(),
+            claimer: RECIPIENT.to_string(),
+            recipient: RECIPIENT.to_string(),
+            token: TOKEN.to_string(),
+            claimed_amount,
+            block_number: 1,
+            transaction_hash,
+            log_index: 0,
+            cost: None,
+        }
+    }
+
+    fn transfer_log(token: Address, from: Address, to: Address, value: u128) -> Log {
+        Log {
+            address: token,
+            data: IERC20::Transfer {
+                from,
+                to,
+                value: U256::from(value),
+            }
+            .encode_log_data(),
+        }
+    }
+
+    #[test]
+    fn test_matched_transfer() {
+        let logs = HashMap::from([(
+            B256::from([1; 32]),
+            vec![transfer_log(TOKEN, COORDINATOR, RECIPIENT, 100)],
+        )]);
+        let report = check_transfers(COORDINATOR, &[claim([1; 32], 100)], &logs).unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.matched, 1);
+        assert!(report.flagged.is_empty());
+    }
+
+    #[test]
+    fn test_amount_mismatch() {
+        let logs = HashMap::from([(
+            B256::from([1; 32]),
+            vec![transfer_log(TOKEN, COORDINATOR, RECIPIENT, 99)],
+        )]);
+        let report = check_transfers(COORDINATOR, &[claim([1; 32], 100)], &logs).unwrap();
+        assert_eq!(report.matched, 0);
+        assert_eq!(
+            report.flagged[0].issue,
+            TransferIssue::AmountMismatch {
+                transferred: U256::from(99)
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_transfer() {
+        let logs = HashMap::from([(
+            B256::from([1; 32]),
+            vec![
+                transfer_log(TOKEN, RECIPIENT, COORDINATOR, 100),
+                transfer_log(Address::repeat_byte(0xdd), COORDINATOR, RECIPIENT, 100),
+            ],
+        )]);
+        let report = check_transfers(COORDINATOR, &[claim([1; 32], 100)], &logs).unwrap();
+        assert_eq!(report.flagged[0].issue, TransferIssue::MissingTransfer);
+    }
+
+    #[test]
+    fn test_missing_receipt() {
+        let report = check_transfers(COORDINATOR, &[claim([1; 32], 100)], &HashMap::new()).unwrap();
+        assert_eq!(report.flagged[0].issue, TransferIssue::MissingReceipt);
+    }
+
+    #[test]
+    fn test_transfer_matches_one_claim() {
+        let logs = HashMap::from([(
+            B256::from([1; 32]),
+            vec![transfer_log(TOKEN, COORDINATOR, RECIPIENT, 100)],
+        )]);
+        let claims = [claim([1; 32], 100), claim([1; 32], 100)];
+        let report = check_transfers(COORDINATOR, &claims, &logs).unwrap();
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.flagged.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_reports() {
+        let logs = HashMap::from([(
+            B256::from([1; 32]),
+            vec![transfer_log(TOKEN, COORDINATOR, RECIPIENT, 100)],
+        )]);
+        let mut report = check_transfers(COORDINATOR, &[claim([1; 32], 100)], &logs).unwrap();
+        report.merge(check_transfers(COORDINATOR, &[claim([2; 32], 100)], &logs).unwrap());
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.flagged[0].issue, TransferIssue::MissingReceipt);
+    }
+}
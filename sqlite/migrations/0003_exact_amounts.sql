@@ -0,0 +1,54 @@
+-- SQLite cannot change a column type, so the tables are rebuilt with amounts as TEXT.  Under
+-- NUMERIC affinity amounts over 2^63 were stored as REAL and already rounded; they carry over
+-- as the nearest integer.  Text amounts do not order numerically, so the claimed amount index
+-- is not recreated.
+CREATE TABLE rewards_claimed_exact (
+    id integer PRIMARY KEY,
+    root CHAR(64),
+    earner CHAR(42),
+    claimer CHAR(42),
+    recipient CHAR(42),
+    token CHAR(42),
+    claimed_amount TEXT
+);
+INSERT INTO rewards_claimed_exact (id, root, earner, claimer, recipient, token, claimed_amount)
+    SELECT id, root, earner, claimer, recipient, token,
+        CASE typeof(claimed_amount) WHEN 'real' THEN printf('%.0f', claimed_amount) ELSE CAST(claimed_amount AS TEXT) END
+    FROM rewards_claimed;
+DROP TABLE rewards_claimed;
+ALTER TABLE rewards_claimed_exact RENAME TO rewards_claimed;
+CREATE INDEX rewards_claimed_id_index ON rewards_claimed (id, root);
+
+CREATE TABLE rewards_submission_exact (
+    id integer PRIMARY KEY,
+    avs CHAR(42),
+    submission_nonce BIGINT,
+    rewards_submission_hash CHAR(64),
+    for_all BOOLEAN,
+    token CHAR(42),
+    amount TEXT,
+    start_timestamp BIGINT,
+    duration BIGINT
+);
+INSERT INTO rewards_submission_exact (id, avs, submission_nonce, rewards_submission_hash, for_all, token, amount, start_timestamp, duration)
+    SELECT id, avs, submission_nonce, rewards_submission_hash, for_all, token,
+        CASE typeof(amount) WHEN 'real' THEN printf('%.0f', amount) ELSE CAST(amount AS TEXT) END,
+        start_timestamp, duration
+    FROM rewards_submission;
+DROP TABLE rewards_submission;
+ALTER TABLE rewards_submission_exact RENAME TO rewards_submission;
+CREATE INDEX rewards_submission_avs_token_index ON rewards_submission (avs, token, start_timestamp);
+
+CREATE TABLE rewards_submission_strategy_exact (
+    id integer PRIMARY KEY,
+    rewards_submission_hash CHAR(64),
+    strategy CHAR(42),
+    multiplier TEXT
+);
+INSERT INTO rewards_submission_strategy_exact (id, rewards_submission_hash, strategy, multiplier)
+    SELECT id, rewards_submission_hash, strategy,
+        CASE typeof(multiplier) WHEN 'real' THEN printf('%.0f', multiplier) ELSE CAST(multiplier AS TEXT) END
+    FROM rewards_submission_strategy;
+DROP TABLE rewards_submission_strategy;
+ALTER TABLE rewards_submission_strategy_exact RENAME TO rewards_submission_strategy;
+CREATE INDEX rewards_submission_strategy_hash_index ON rewards_submission_strategy (rewards_submission_hash);
@@ -0,0 +1,6 @@
+-- What each claim's transaction cost, when the indexer fetched its receipt.  The fee paid is the
+-- claim's share of the transaction fee, so fees sum to what claiming cost.  The gas price and fee
+-- are decimal text like the amounts.
+ALTER TABLE rewards_claimed ADD COLUMN gas_used BIGINT;
+ALTER TABLE rewards_claimed ADD COLUMN effective_gas_price TEXT;
+ALTER TABLE rewards_claimed ADD COLUMN fee_paid TEXT;
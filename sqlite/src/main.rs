@@ -1,18 +1,24 @@
-mod sq;
+use sqlite::sq;
 
 #[tokio::main]
 async fn main() {
     init_logging();
 
+    // connect db
+    let mut sq_client = sq::init().await.unwrap();
+    if std::env::args().nth(1).as_deref() == Some("migrate") {
+        migrate(&mut sq_client).await.unwrap();
+        return;
+    }
+    sq::migrate_up(&mut sq_client).await.unwrap();
+
     // File paths
     let json_file_path = "rewards_claimed.json";
+    let submission_file_path = "rewards_submissions.json";
 
     // Read the JSON file
     let rewards_claimed = read_reward_json(json_file_path).await.unwrap();
-
-    // connect db
-    let mut sq_client = sq::init().await.unwrap();
-    sq::create_tables(&mut sq_client).await.unwrap();
+    let rewards_submissions = read_submission_json(submission_file_path).await.unwrap();
 
     let write_instant = std::time::Instant::now();
     // Write the pg data
@@ -20,6 +26,9 @@ async fn main() {
         .await
         .unwrap();
     println!("Write time: {:?} us", write_instant.elapsed().as_micros());
+    sq::write_rewards_submissions(&mut sq_client, &rewards_submissions)
+        .await
+        .unwrap();
 
     let read_instant = std::time::Instant::now();
     let stat = sq::read_rewards_claimed_stat(&mut sq_client).await.unwrap();
@@ -28,6 +37,12 @@ async fn main() {
         read_instant.elapsed().as_micros()
     );
     println!("Claimed amount stat: {:?}", stat);
+    let cost_stat = sq::read_claim_cost_stat(&mut sq_client).await.unwrap();
+    println!("Claim cost stat: {:?}", cost_stat);
+    let offered = sq::read_rewards_offered(&mut sq_client, 0, u32::MAX)
+        .await
+        .unwrap();
+    println!("Rewards offered: {:?}", offered);
 
     println!(
         "Table size in postgres: {:?}",
@@ -35,6 +50,32 @@ async fn main() {
     );
 }
 
+/// `sqlite migrate up` applies the pending migrations, `sqlite migrate status` lists them.
+async fn migrate(sq_client: &mut sq::SqliteClient) -> eyre::Result<()> {
+    match std::env::args().nth(2).as_deref() {
+        Some("up") => {
+            let applied = sq::migrate_up(sq_client).await?;
+            println!("Applied migrations: {:?}", applied);
+        }
+        Some("status") => {
+            for migration in sq::migration_status(sq_client).await? {
+                println!(
+                    "{:04} {:<24} {}",
+                    migration.version,
+                    migration.name,
+                    if migration.applied {
+                        "applied"
+                    } else {
+                        "pending"
+                    }
+                );
+            }
+        }
+        _ => return Err(eyre::eyre!("Usage: sqlite migrate up|status")),
+    }
+    Ok(())
+}
+
 fn init_logging() {
     tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -49,3 +90,11 @@ async fn read_reward_json(path: &str) -> eyre::Result<Vec<eigen_types::RewardsCl
     println!("{:?}", rewards_claimed.len());
     Ok(rewards_claimed)
 }
+
+async fn read_submission_json(path: &str) -> eyre::Result<Vec<eigen_types::RewardsSubmission>> {
+    let rewards_submissions = std::fs::read_to_string(path)?;
+    let rewards_submissions: Vec<eigen_types::RewardsSubmission> =
+        serde_json::from_str(&rewards_submissions)?;
+    println!("{:?}", rewards_submissions.len());
+    Ok(rewards_submissions)
+}
@@ -1,13 +1,26 @@
-use rusqlite::{Connection, Error, Result};
+use std::{collections::BTreeSet, str::FromStr};
 
-#[derive(Debug)]
+use alloy::primitives::U256;
+
+use rusqlite::{params, Connection, Error, Result};
+
+#[derive(Debug, Default)]
 #[allow(dead_code)]
 pub struct ClaimedAmountStat {
-    pub sum: f64,
+    pub sum: U256,
     pub count: u64,
     pub claimed_mean: f64,
-    pub claimed_max: f64,
-    pub claimed_min: f64,
+    pub claimed_max: U256,
+    pub claimed_min: U256,
+}
+
+/// Total rewards offered by an AVS in a token over a time window.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct RewardsOffered {
+    pub avs: String,
+    pub token: String,
+    pub total: U256,
 }
 
 /// A wrapper around `tokio_postgres::Client` for working with PostgreSQL database.
@@ -26,6 +39,12 @@ pub async fn init() -> eyre::Result<SqliteClient, Error> {
     Ok(SqliteClient { client: conn })
 }
 
+/// Open a SQLite database file at `path`.
+pub async fn open(path: &str) -> eyre::Result<SqliteClient, Error> {
+    let conn = Connection::open(path)?;
+    Ok(SqliteClient { client: conn })
+}
+
 fn array_to_hex_string(array: &[u8; 32]) -> String {
     let mut hex_string = String::new();
     for byte in array {
@@ -34,104 +53,300 @@ fn array_to_hex_string(array: &[u8; 32]) -> String {
     hex_string
 }
 
-pub async fn create_tables(duck: &mut SqliteClient) -> Result<(), Error> {
-    let _ = duck
-        .client
-        .execute("DROP INDEX IF EXISTS rewards_claimed_id_index", [])?;
-    let _ = duck
-        .client
-        .execute("DROP INDEX IF EXISTS rewards_claimed_claimed_index", [])?;
-    let _ = duck
-        .client
-        .execute("DROP TABLE IF EXISTS rewards_claimed", [])?;
-    let _ = duck.client.execute(
-        "CREATE TABLE IF NOT EXISTS rewards_claimed (
-                id integer PRIMARY KEY,
-                root CHAR(64),
-                earner CHAR(42),
-                claimer CHAR(42),
-                recipient CHAR(42),
-                token CHAR(42),
-                claimed_amount NUMERIC
+/// A schema change, applied once and in version order.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// The schema as versioned changes.  The first only creates what is missing, so it adopts a
+/// database created before migrations were tracked.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_tables",
+        sql: include_str!("../migrations/0001_create_tables.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "indexer_checkpoint",
+        sql: include_str!("../migrations/0002_indexer_checkpoint.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "exact_amounts",
+        sql: include_str!("../migrations/0003_exact_amounts.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "event_identity",
+        sql: include_str!("../migrations/0004_event_identity.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "claim_costs",
+        sql: include_str!("../migrations/0005_claim_costs.sql"),
+    },
+];
+
+/// Whether a migration has been applied to the database.
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied: bool,
+}
+
+fn applied_migrations(client: &Connection) -> Result<BTreeSet<i64>, Error> {
+    client.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
             )",
         [],
     )?;
-    let _ = duck.client.execute(
-        "CREATE INDEX IF NOT EXISTS rewards_claimed_id_index ON rewards_claimed (id, root)",
-        [],
-    )?;
-    let _ = duck
-        .client
-        .execute(
-            "CREATE INDEX IF NOT EXISTS rewards_claimed_claimed_index ON rewards_claimed (claimed_amount)",
-            [],
+    let mut statement = client.prepare("SELECT version FROM schema_migrations")?;
+    let versions = statement.query_map([], |row| row.get(0))?;
+    versions.collect()
+}
+
+/// Apply the migrations not yet recorded in `schema_migrations`, each in its own transaction,
+/// and return the versions applied.  Existing rows are kept.
+pub async fn migrate_up(quack: &mut SqliteClient) -> Result<Vec<i64>, Error> {
+    let applied = applied_migrations(&quack.client)?;
+    let mut versions = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+        let transaction = quack.client.transaction()?;
+        transaction.execute_batch(migration.sql)?;
+        transaction.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+            params![migration.version, migration.name],
         )?;
-    Ok(())
+        transaction.commit()?;
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+pub async fn migration_status(quack: &mut SqliteClient) -> Result<Vec<MigrationStatus>, Error> {
+    let applied = applied_migrations(&quack.client)?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied: applied.contains(&migration.version),
+        })
+        .collect())
 }
 
 pub async fn write_rewards_claimed(
     quack: &mut SqliteClient,
-    rewards_claimed: &Vec<eigen_types::RewardsClaimed>,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+) -> Result<(), Error> {
+    insert_rewards_claimed(&quack.client, rewards_claimed)
+}
+
+fn insert_rewards_claimed(
+    client: &Connection,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
 ) -> Result<(), Error> {
     for reward in rewards_claimed {
-        if reward.claimed_amount > 5000u128 * 10u128.pow(18) {
-            // warn!("Claimed amount too high: {:?}", reward.claimed_amount);
+        // claims without their log are written without an identity and never conflict
+        let event = reward.has_event_id();
+        let cost = reward.cost.as_ref();
+        client.execute(
+            "INSERT INTO rewards_claimed (root, earner, claimer, recipient, token, claimed_amount, block_number, transaction_hash, log_index, gas_used, effective_gas_price, fee_paid) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT (transaction_hash, log_index) DO NOTHING",
+            params![
+                array_to_hex_string(&reward.root),
+                reward.earner,
+                reward.claimer,
+                reward.recipient,
+                reward.token,
+                reward.claimed_amount.to_string(),
+                event.then_some(reward.block_number as i64),
+                event.then(|| array_to_hex_string(&reward.transaction_hash)),
+                event.then_some(reward.log_index as i64),
+                cost.map(|cost| cost.gas_used as i64),
+                cost.map(|cost| cost.effective_gas_price.to_string()),
+                cost.map(|cost| cost.fee_paid.to_string()),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+pub async fn write_rewards_submissions(
+    quack: &mut SqliteClient,
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+) -> Result<(), Error> {
+    insert_rewards_submissions(&quack.client, rewards_submissions)
+}
+
+fn insert_rewards_submissions(
+    client: &Connection,
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+) -> Result<(), Error> {
+    for submission in rewards_submissions {
+        let rewards_submission_hash = array_to_hex_string(&submission.rewards_submission_hash);
+        let inserted = client.execute(
+            "INSERT INTO rewards_submission (avs, submission_nonce, rewards_submission_hash, for_all, token, amount, start_timestamp, duration) VALUES (?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT (rewards_submission_hash) DO NOTHING",
+            params![
+                submission.avs,
+                submission.submission_nonce as i64,
+                rewards_submission_hash,
+                submission.for_all,
+                submission.token,
+                submission.amount.to_string(),
+                submission.start_timestamp as i64,
+                submission.duration as i64,
+            ],
+        )?;
+        if inserted == 0 {
+            // already written, along with its strategies
             continue;
         }
-        let claimed_amount: u128 = reward.claimed_amount;
-        let _ = quack
-            .client
-            .execute(
-                "INSERT INTO rewards_claimed (root, earner, claimer, recipient, token, claimed_amount) VALUES (?, ?, ?, ?, ?, ?)",
-                [
-                    &array_to_hex_string(&reward.root),
-                    &reward.earner,
-                    &reward.claimer,
-                    &reward.recipient,
-                    &reward.token,
-                    &claimed_amount.to_string(),
+        for strategy in &submission.strategies_and_multipliers {
+            client.execute(
+                "INSERT INTO rewards_submission_strategy (rewards_submission_hash, strategy, multiplier) VALUES (?, ?, ?)",
+                params![
+                    rewards_submission_hash,
+                    strategy.strategy,
+                    strategy.multiplier.to_string(),
                 ],
-            );
+            )?;
+        }
     }
     Ok(())
 }
 
-pub async fn read_rewards_claimed_stat(
-    quack: &mut SqliteClient,
-) -> eyre::Result<ClaimedAmountStat> {
-    let sum: f64 = quack.client.query_row(
-        "SELECT SUM(claimed_amount) FROM rewards_claimed",
-        [],
-        |row| row.get(0),
-    )?;
+/// The last block committed by the indexer, if any.  Needs the migrations applied.
+pub async fn read_checkpoint(quack: &mut SqliteClient) -> eyre::Result<Option<u64>> {
+    let mut statement = quack
+        .client
+        .prepare("SELECT block_number FROM indexer_checkpoint WHERE id = 1")?;
+    let mut rows = statement.query([])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get::<_, i64>(0)? as u64)),
+        None => Ok(None),
+    }
+}
 
-    let count: u64 = quack.client.query_row(
-        "SELECT COUNT(claimed_amount) FROM rewards_claimed",
-        [],
-        |row| row.get(0),
+/// Write a batch of indexed events and advance the checkpoint to `block_number` in one transaction.
+pub async fn write_batch(
+    quack: &mut SqliteClient,
+    rewards_claimed: &[eigen_types::RewardsClaimed],
+    rewards_submissions: &[eigen_types::RewardsSubmission],
+    block_number: u64,
+) -> Result<(), Error> {
+    let transaction = quack.client.transaction()?;
+    insert_rewards_claimed(&transaction, rewards_claimed)?;
+    insert_rewards_submissions(&transaction, rewards_submissions)?;
+    transaction.execute(
+        "INSERT INTO indexer_checkpoint (id, block_number) VALUES (1, ?)
+            ON CONFLICT (id) DO UPDATE SET block_number = excluded.block_number",
+        params![block_number as i64],
     )?;
+    transaction.commit()
+}
 
-    let claimed_max: f64 = quack.client.query_row(
-        "SELECT MAX(claimed_amount) FROM rewards_claimed",
-        [],
-        |row| row.get(0),
-    )?;
+/// An amount stored as decimal text.
+fn parse_amount(amount: &str) -> eyre::Result<U256> {
+    U256::from_str(amount).map_err(|e| eyre::eyre!("Invalid amount {:?}: {}", amount, e))
+}
 
-    let claimed_min: f64 = quack.client.query_row(
-        "SELECT MIN(claimed_amount) FROM rewards_claimed",
-        [],
-        |row| row.get(0),
+/// Total rewards offered per AVS and token for submissions starting in `[from_timestamp, to_timestamp)`.
+/// Amounts are stored as text, so they are summed here rather than in SQL.
+pub async fn read_rewards_offered(
+    quack: &mut SqliteClient,
+    from_timestamp: u32,
+    to_timestamp: u32,
+) -> eyre::Result<Vec<RewardsOffered>> {
+    let mut statement = quack.client.prepare(
+        "SELECT avs, token, amount FROM rewards_submission
+            WHERE start_timestamp >= ? AND start_timestamp < ?
+            ORDER BY avs, token",
     )?;
+    let mut rows = statement.query(params![from_timestamp as i64, to_timestamp as i64])?;
+    let mut offered: Vec<RewardsOffered> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let avs: String = row.get(0)?;
+        let token: String = row.get(1)?;
+        let amount = parse_amount(&row.get::<_, String>(2)?)?;
+        match offered.last_mut() {
+            Some(last) if last.avs == avs && last.token == token => {
+                last.total = last
+                    .total
+                    .checked_add(amount)
+                    .ok_or_else(|| eyre::eyre!("Rewards offered overflow"))?;
+            }
+            _ => offered.push(RewardsOffered {
+                avs,
+                token,
+                total: amount,
+            }),
+        }
+    }
+    Ok(offered)
+}
 
-    let claimed_mean = sum / count as f64;
+/// Claimed amount statistics, computed exactly over the text amounts.
+pub async fn read_rewards_claimed_stat(
+    quack: &mut SqliteClient,
+) -> eyre::Result<ClaimedAmountStat> {
+    let mut statement = quack
+        .client
+        .prepare("SELECT claimed_amount FROM rewards_claimed WHERE claimed_amount IS NOT NULL")?;
+    let mut rows = statement.query([])?;
+    let mut stat = ClaimedAmountStat {
+        claimed_min: U256::MAX,
+        ..Default::default()
+    };
+    while let Some(row) = rows.next()? {
+        let claimed_amount = parse_amount(&row.get::<_, String>(0)?)?;
+        stat.sum = stat
+            .sum
+            .checked_add(claimed_amount)
+            .ok_or_else(|| eyre::eyre!("Claimed amount sum overflow"))?;
+        stat.count += 1;
+        stat.claimed_max = stat.claimed_max.max(claimed_amount);
+        stat.claimed_min = stat.claimed_min.min(claimed_amount);
+    }
+    if stat.count == 0 {
+        return Ok(ClaimedAmountStat::default());
+    }
+    stat.claimed_mean = f64::from(stat.sum) / stat.count as f64;
+    Ok(stat)
+}
 
-    Ok(ClaimedAmountStat {
-        sum,
-        count,
-        claimed_mean,
-        claimed_max,
-        claimed_min,
-    })
+/// Fee per claim and fee over claimed value, over the claims with a cost.
+pub async fn read_claim_cost_stat(
+    quack: &mut SqliteClient,
+) -> eyre::Result<eigen_types::ClaimCostStat> {
+    let mut statement = quack.client.prepare(
+        "SELECT claimed_amount, fee_paid FROM rewards_claimed
+            WHERE claimed_amount IS NOT NULL AND fee_paid IS NOT NULL",
+    )?;
+    let mut rows = statement.query([])?;
+    let mut fees = Vec::new();
+    while let Some(row) = rows.next()? {
+        let claimed_amount = row.get::<_, String>(0)?;
+        let fee_paid = row.get::<_, String>(1)?;
+        fees.push((
+            claimed_amount
+                .parse()
+                .map_err(|e| eyre::eyre!("Invalid amount {:?}: {}", claimed_amount, e))?,
+            fee_paid
+                .parse()
+                .map_err(|e| eyre::eyre!("Invalid fee {:?}: {}", fee_paid, e))?,
+        ));
+    }
+    Ok(eigen_types::ClaimCostStat::from_fees(fees))
 }
 
 pub async fn table_size(quack: &mut SqliteClient) -> eyre::Result<u64> {
@@ -143,3 +358,229 @@ pub async fn table_size(quack: &mut SqliteClient) -> eyre::Result<u64> {
             })?;
     Ok(size)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVS: &str = "0x3B78576F7D6837500bA3De27A60c7f594934027E";
+    const TOKEN: &str = "0xAcc1fb458a1317E886dB376Fc8141540537E68fE";
+
+    fn claim(log_index: u64, claimed_amount: u128) -> eigen_types::RewardsClaimed {
+        eigen_types::RewardsClaimed {
+            root: [1; 32],
+            earner: AVS.to_string(),
+            claimer: AVS.to_string(),
+            recipient: AVS.to_string(),
+            token: TOKEN.to_string(),
+            claimed_amount,
+            block_number: 10,
+            transaction_hash: [2; 32],
+            log_index,
+            cost: None,
+        }
+    }
+
+    fn submission(submission_nonce: u64, amount: u128) -> eigen_types::RewardsSubmission {
+        eigen_types::RewardsSubmission {
+            avs: AVS.to_string(),
+            submission_nonce,
+            rewards_submission_hash: [submission_nonce as u8; 32],
+            for_all: false,
+            strategies_and_multipliers: vec![eigen_types::StrategyAndMultiplier {
+                strategy: TOKEN.to_string(),
+                multiplier: u128::MAX,
+            }],
+            token: TOKEN.to_string(),
+            amount,
+            start_timestamp: 1720656000,
+            duration: 604800,
+        }
+    }
+
+    async fn migrated() -> SqliteClient {
+        let mut quack = SqliteClient {
+            client: Connection::open_in_memory().unwrap(),
+        };
+        migrate_up(&mut quack).await.unwrap();
+        quack
+    }
+
+    #[tokio::test]
+    async fn test_amounts_are_exact() {
+        let mut quack = migrated().await;
+        write_rewards_claimed(
+            &mut quack,
+            &[claim(0, u128::MAX), claim(1, u128::MAX), claim(2, 1)],
+        )
+        .await
+        .unwrap();
+        let stat = read_rewards_claimed_stat(&mut quack).await.unwrap();
+        assert_eq!(stat.count, 3);
+        assert_eq!(
+            stat.sum,
+            U256::from(u128::MAX) * U256::from(2) + U256::from(1)
+        );
+        assert_eq!(stat.claimed_max, U256::from(u128::MAX));
+        assert_eq!(stat.claimed_min, U256::from(1));
+
+        write_rewards_submissions(
+            &mut quack,
+            &[submission(1, u128::MAX), submission(2, u128::MAX)],
+        )
+        .await
+        .unwrap();
+        let offered = read_rewards_offered(&mut quack, 0, u32::MAX).await.unwrap();
+        assert_eq!(offered.len(), 1);
+        assert_eq!(offered[0].avs, AVS);
+        assert_eq!(offered[0].total, U256::from(u128::MAX) * U256::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_leaves_checkpoint() {
+        let mut quack = migrated().await;
+        write_batch(&mut quack, &[claim(0, 5)], &[], 10)
+            .await
+            .unwrap();
+        assert_eq!(read_checkpoint(&mut quack).await.unwrap(), Some(10));
+
+        quack
+            .client
+            .execute_batch(
+                "CREATE TRIGGER reject_claim BEFORE INSERT ON rewards_claimed
+                    WHEN NEW.claimed_amount = '13'
+                    BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+            )
+            .unwrap();
+        let result = write_batch(&mut quack, &[claim(1, 7), claim(2, 13)], &[], 20).await;
+        assert!(result.is_err());
+        assert_eq!(read_checkpoint(&mut quack).await.unwrap(), Some(10));
+        // the claim inserted before the failure is rolled back with the checkpoint
+        let stat = read_rewards_claimed_stat(&mut quack).await.unwrap();
+        assert_eq!(stat.count, 1);
+        assert_eq!(stat.sum, U256::from(5));
+    }
+
+    fn count(quack: &SqliteClient, table: &str) -> i64 {
+        quack
+            .client
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrations_apply_once() {
+        let mut quack = migrated().await;
+        assert!(migrate_up(&mut quack).await.unwrap().is_empty());
+        let status = migration_status(&mut quack).await.unwrap();
+        assert_eq!(status.len(), MIGRATIONS.len());
+        assert!(status.iter().all(|migration| migration.applied));
+    }
+
+    #[tokio::test]
+    async fn test_event_identity_keeps_existing_rows() {
+        let mut quack = SqliteClient {
+            client: Connection::open_in_memory().unwrap(),
+        };
+        applied_migrations(&quack.client).unwrap();
+        for migration in &MIGRATIONS[..3] {
+            quack.client.execute_batch(migration.sql).unwrap();
+            quack
+                .client
+                .execute(
+                    "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+                    params![migration.version, migration.name],
+                )
+                .unwrap();
+        }
+        // a version 3 database where a rerun wrote the same claim and submission twice
+        let legacy = format!(
+            "INSERT INTO rewards_claimed (root, earner, claimer, recipient, token, claimed_amount)
+                    VALUES ('01', '{avs}', '{avs}', '{avs}', '{token}', '5');
+                INSERT INTO rewards_submission (avs, submission_nonce, rewards_submission_hash, for_all, token, amount, start_timestamp, duration)
+                    VALUES ('{avs}', 1, 'aa', false, '{token}', '5', 1, 1);
+                INSERT INTO rewards_submission_strategy (rewards_submission_hash, strategy, multiplier)
+                    VALUES ('aa', '{token}', '1');",
+            avs = AVS,
+            token = TOKEN,
+        );
+        quack.client.execute_batch(&legacy).unwrap();
+        quack.client.execute_batch(&legacy).unwrap();
+
+        assert_eq!(migrate_up(&mut quack).await.unwrap(), vec![4, 5]);
+        // claims without an identity are kept, the copy of the submission is removed
+        assert_eq!(count(&quack, "rewards_claimed"), 2);
+        assert_eq!(count(&quack, "rewards_submission"), 1);
+        assert_eq!(count(&quack, "rewards_submission_strategy"), 1);
+        let offered = read_rewards_offered(&mut quack, 0, u32::MAX).await.unwrap();
+        assert_eq!(offered[0].total, U256::from(5));
+    }
+
+    #[tokio::test]
+    async fn test_rerun_inserts_nothing_twice() {
+        let mut quack = migrated().await;
+        let claims = [claim(0, 5), claim(1, 7)];
+        let submissions = [submission(1, 11)];
+        write_batch(&mut quack, &claims, &submissions, 10)
+            .await
+            .unwrap();
+        write_batch(&mut quack, &claims, &submissions, 10)
+            .await
+            .unwrap();
+        let stat = read_rewards_claimed_stat(&mut quack).await.unwrap();
+        assert_eq!(stat.count, 2);
+        assert_eq!(stat.sum, U256::from(12));
+        assert_eq!(count(&quack, "rewards_submission"), 1);
+        assert_eq!(count(&quack, "rewards_submission_strategy"), 1);
+
+        // claims without their log cannot be told apart, so each is kept
+        let legacy = eigen_types::RewardsClaimed {
+            transaction_hash: [0; 32],
+            ..claim(0, 3)
+        };
+        write_rewards_claimed(&mut quack, &[legacy.clone(), legacy])
+            .await
+            .unwrap();
+        assert_eq!(count(&quack, "rewards_claimed"), 4);
+    }
+
+    #[tokio::test]
+    async fn test_claim_cost_stat() {
+        let mut quack = migrated().await;
+        let cost = |fee_paid| {
+            Some(eigen_types::ClaimCost {
+                gas_used: 150_000,
+                effective_gas_price: 10_000_000_000,
+                fee_paid,
+            })
+        };
+        let claims = [
+            eigen_types::RewardsClaimed {
+                cost: cost(750_000_000_000_000),
+                ..claim(0, 1_000_000_000_000_000_000)
+            },
+            eigen_types::RewardsClaimed {
+                cost: cost(750_000_000_000_000),
+                ..claim(1, 3_000_000_000_000_000_000)
+            },
+            claim(2, 5),
+        ];
+        write_batch(&mut quack, &claims, &[], 10).await.unwrap();
+        let stat = read_claim_cost_stat(&mut quack).await.unwrap();
+        assert_eq!(stat, eigen_types::ClaimCostStat::from_claims(&claims));
+        assert_eq!(stat.count, 2);
+        assert_eq!(stat.total_fee, 1_500_000_000_000_000);
+
+        let stored: (i64, String) = quack
+            .client
+            .query_row(
+                "SELECT gas_used, effective_gas_price FROM rewards_claimed WHERE log_index = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(stored, (150_000, "10000000000".to_string()));
+    }
+}